@@ -121,7 +121,7 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             model.world = Some(create_world(model));
             log!("world created");
             log!("starting game loop...");
-            orders.after_next_render(Msg::OnTick);
+            orders.stream(streams::animation_frame(Msg::OnTick));
         }
         Msg::OnResize => {
             let (stage_width, stage_height) = get_window_size(&window()).unwrap();
@@ -146,7 +146,6 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 model.fps = hud.fps();
                 model.num_bunnies = hud.num_bunnies();
             }
-            orders.after_next_render(Msg::OnTick);
         }
         Msg::PointerDown => {
             if let Some(world) = &model.world {
@@ -5,6 +5,8 @@ use seed::{prelude::*, *};
 mod page;
 
 const ADMIN: &str = "admin";
+/// Urls are stored in the hash - `/#/admin/report/daily`.
+pub const ROUTING_MODE: RoutingMode = RoutingMode::Hash;
 
 // ------ ------
 //     Init
@@ -16,7 +18,7 @@ fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
         ctx: Context {
             logged_user: "John Doe",
         },
-        base_url: url.to_hash_base_url(),
+        base_url: url.to_base_url(),
         page: Page::init(url),
     }
 }
@@ -47,7 +49,7 @@ enum Page {
 
 impl Page {
     fn init(mut url: Url) -> Self {
-        match url.next_hash_path_part() {
+        match url.next_path_part() {
             None => Self::Home,
             Some(ADMIN) => page::admin::init(url).map_or(Self::NotFound, Self::Admin),
             Some(_) => Self::NotFound,
@@ -65,7 +67,7 @@ impl<'a> Urls<'a> {
         self.base_url()
     }
     pub fn admin_urls(self) -> page::admin::Urls<'a> {
-        page::admin::Urls::new(self.base_url().add_hash_path_part(ADMIN))
+        page::admin::Urls::new(self.base_url().add_path_part(ADMIN))
     }
 }
 
@@ -103,11 +105,13 @@ fn view(model: &Model) -> impl IntoNodes<Msg> {
 fn header(base_url: &Url) -> Node<Msg> {
     ul![
         li![a![
-            attrs! { At::Href => Urls::new(base_url).home() },
+            attrs! { At::Href => Urls::new(base_url).home().to_href(ROUTING_MODE) },
             "Home",
         ]],
         li![a![
-            attrs! { At::Href => Urls::new(base_url).admin_urls().report_urls().default() },
+            attrs! {
+                At::Href => Urls::new(base_url).admin_urls().report_urls().default().to_href(ROUTING_MODE)
+            },
             "Report",
         ]],
     ]
@@ -119,5 +123,5 @@ fn header(base_url: &Url) -> Node<Msg> {
 
 #[wasm_bindgen(start)]
 pub fn start() {
-    App::start("app", init, update, view);
+    App::start_with_routing_mode("app", init, update, view, ROUTING_MODE);
 }
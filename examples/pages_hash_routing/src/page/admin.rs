@@ -11,7 +11,7 @@ mod page;
 
 pub fn init(mut url: Url) -> Option<Model> {
     Some(Model {
-        report_page: match url.next_hash_path_part() {
+        report_page: match url.next_path_part() {
             Some(REPORT) => page::report::init(url)?,
             _ => None?,
         },
@@ -33,7 +33,7 @@ pub struct Model {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn report_urls(self) -> page::report::Urls<'a> {
-        page::report::Urls::new(self.base_url().add_hash_path_part(REPORT))
+        page::report::Urls::new(self.base_url().add_path_part(REPORT))
     }
 }
 
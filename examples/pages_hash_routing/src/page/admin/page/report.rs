@@ -1,4 +1,4 @@
-use crate::Context;
+use crate::{Context, ROUTING_MODE};
 use seed::{prelude::*, *};
 
 const DAILY: &str = "daily";
@@ -9,9 +9,9 @@ const WEEKLY: &str = "weekly";
 // ------ ------
 
 pub fn init(mut url: Url) -> Option<Model> {
-    let base_url = url.to_hash_base_url();
+    let base_url = url.to_base_url();
 
-    let frequency = match url.remaining_hash_path_parts().as_slice() {
+    let frequency = match url.remaining_path_parts().as_slice() {
         [] => {
             Urls::new(&base_url).default().go_and_replace();
             Frequency::default()
@@ -59,10 +59,10 @@ impl<'a> Urls<'a> {
         self.daily()
     }
     pub fn daily(self) -> Url {
-        self.base_url().add_hash_path_part(DAILY)
+        self.base_url().add_path_part(DAILY)
     }
     pub fn weekly(self) -> Url {
-        self.base_url().add_hash_path_part(WEEKLY)
+        self.base_url().add_path_part(WEEKLY)
     }
 }
 
@@ -77,7 +77,7 @@ pub fn view<Ms>(model: &Model, ctx: &Context) -> Node<Ms> {
             a![
                 "Switch to weekly",
                 attrs! {
-                    At::Href => Urls::new(&model.base_url).weekly()
+                    At::Href => Urls::new(&model.base_url).weekly().to_href(ROUTING_MODE)
                 }
             ],
         ),
@@ -86,7 +86,7 @@ pub fn view<Ms>(model: &Model, ctx: &Context) -> Node<Ms> {
             a![
                 "Switch to daily",
                 attrs! {
-                    At::Href => Urls::new(&model.base_url).daily()
+                    At::Href => Urls::new(&model.base_url).daily().to_href(ROUTING_MODE)
                 }
             ],
         ),
@@ -10,12 +10,7 @@ const STORAGE_KEY: &str = "seed_unsaved_changes_text";
 // ------ ------
 
 #[allow(clippy::needless_pass_by_value)]
-fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
-    // https://developer.mozilla.org/en-US/docs/Web/API/WindowEventHandlers/onbeforeunload
-    orders
-        .stream(streams::window_event(Ev::BeforeUnload, Msg::BeforeUnload))
-        .subscribe(Msg::UrlRequested);
-
+fn init(url: Url, _: &mut impl Orders<Msg>) -> Model {
     let text = LocalStorage::get(STORAGE_KEY).unwrap_or_default();
     Model {
         base_url: url.to_base_url(),
@@ -55,8 +50,6 @@ impl<'a> Urls<'a> {
 enum Msg {
     TextChanged(String),
     Save,
-    UrlRequested(subs::UrlRequested),
-    BeforeUnload(web_sys::Event),
 }
 
 fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
@@ -66,26 +59,24 @@ fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
             LocalStorage::insert(STORAGE_KEY, &model.text).expect("save text");
             model.saved_text_hash = calculate_hash(&model.text);
         }
-        Msg::UrlRequested(subs::UrlRequested(_, url_request)) => {
-            if calculate_hash(&model.text) == model.saved_text_hash {
-                return;
-            }
-            if let Ok(true) =
-                window().confirm_with_message("Do you want to leave? Data won't be saved.")
-            {
-                return;
-            }
-            url_request.handled_and_prevent_refresh();
-        }
-        Msg::BeforeUnload(event) => {
-            if calculate_hash(&model.text) != model.saved_text_hash {
-                log!("attempt to prevent navigation");
-                let event = event.unchecked_into::<web_sys::BeforeUnloadEvent>();
-                event.prevent_default();
-                // Because of Chrome
-                event.set_return_value("");
-            }
+    }
+}
+
+// ------ ------
+//  Navigation guard
+// ------ ------
+
+fn navigation_guard(model: &Model, navigation: &Navigation) -> NavigationGuard {
+    if calculate_hash(&model.text) == model.saved_text_hash {
+        return NavigationGuard::Allow;
+    }
+    match navigation {
+        // https://developer.mozilla.org/en-US/docs/Web/API/WindowEventHandlers/onbeforeunload
+        Navigation::Unload => {
+            log!("attempt to prevent navigation");
+            NavigationGuard::Block
         }
+        _ => NavigationGuard::Confirm("Do you want to leave? Data won't be saved.".to_owned()),
     }
 }
 
@@ -132,5 +123,5 @@ fn view(model: &Model) -> Node<Msg> {
 
 #[wasm_bindgen(start)]
 pub fn start() {
-    App::start("app", init, update, view);
+    App::start("app", init, update, view).set_navigation_guard(navigation_guard);
 }
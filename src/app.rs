@@ -1,47 +1,73 @@
-use crate::browser::dom::virtual_dom_bridge;
+use crate::browser::dom::{virtual_dom_bridge, Stylesheet};
 use crate::browser::{
     service::routing,
-    util::{self, window, ClosureNew},
-    Url, DUMMY_BASE_URL,
+    util::{self, window},
+    RoutingMode, Url, DUMMY_BASE_URL,
 };
-use crate::virtual_dom::{patch, El, EventHandlerManager, IntoNodes, Mailbox, Node, Tag};
+use crate::virtual_dom::{lifecycle_hook, patch, El, EventHandlerManager, IntoNodes, Mailbox, Tag};
 use enclose::{enc, enclose};
+use futures::{channel::oneshot, future::FutureExt};
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
     any::Any,
     cell::{Cell, RefCell},
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fmt,
     rc::Rc,
 };
-use sub_manager::SubManager;
-use wasm_bindgen::closure::Closure;
+use sub_manager::{Propagation, SubManager};
+use wasm_bindgen::{JsCast, JsValue};
 
 pub mod cfg;
 pub mod cmd_manager;
 pub mod cmds;
+pub mod component;
+pub mod context;
 pub mod data;
 mod effect;
 pub mod get_element;
+pub mod handler_output;
+pub mod initial_state;
 pub mod message_mapper;
+pub mod navigation_guard;
 pub mod orders;
 pub mod render_info;
+pub mod render_scheduler;
+pub mod scroll_restoration;
 pub mod stream_manager;
 pub mod streams;
 pub mod sub_manager;
 pub mod subs;
+pub mod subscriptions;
+pub mod time_travel;
+pub mod web_component;
 
 pub use cfg::AppCfg;
 pub use cmd_manager::CmdHandle;
+pub use component::{component, Component, ComponentMsg};
+pub use context::context;
+use context::Reader;
 pub(crate) use data::AppData;
-use effect::Effect;
+pub(crate) use effect::Effect;
 pub use get_element::GetElement;
+pub use handler_output::HandlerOutput;
 pub use message_mapper::MessageMapper;
+pub use navigation_guard::{Navigation, NavigationGuard};
+use orders::MappedOrders;
 pub use orders::{Orders, OrdersContainer, OrdersProxy};
 pub use render_info::RenderInfo;
+pub use render_scheduler::RenderScheduler;
+use scroll_restoration::Scroll;
+pub use scroll_restoration::ScrollRestoration;
 pub use stream_manager::StreamHandle;
 pub use sub_manager::{Notification, SubHandle};
+pub use subscriptions::Subs;
+use subscriptions::{ActiveSub, Sub};
+pub use time_travel::TimeTravel;
+pub use web_component::WebComponent;
 
 /// Determines if an update should cause the `VDom` to rerender or not.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ShouldRender {
     Render,
     ForceRenderNow,
@@ -91,7 +117,8 @@ where
 {
     /// Create, mount and start the `App`. It's the standard way to create a Seed app.
     ///
-    /// _NOTE:_ It tries to hydrate the root element content => you can use it also for prerendered website.
+    /// _NOTE:_ It hydrates the root element content - existing DOM nodes are reused and patched
+    /// where they differ from the first `view` output => you can use it also for prerendered website.
     ///
     /// # Example
     ///
@@ -122,7 +149,8 @@ where
     ///#[wasm_bindgen(start)]
     /// pub fn start() {
     ///     // Mount to the root element with id "app".
-    ///     // You can pass also `web_sys::Element` or `web_sys::HtmlElement` as a root element.
+    ///     // You can pass also `web_sys::Element`, `web_sys::HtmlElement` or `web_sys::ShadowRoot`
+    ///     // (see `Stylesheet` for scoped styles) as a root element.
     ///     // It's NOT recommended to mount into body or into elements which contain scripts.
     ///     App::start("app", init, update, view);
     /// }
@@ -138,6 +166,44 @@ where
         init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
         update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
         view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Self {
+        Self::start_with_routing(root_element, init, update, view, Some(RoutingMode::Path))
+    }
+
+    /// Like `App::start`, but the app's `Url` is stored according to `routing_mode` -
+    /// see `RoutingMode`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///// Urls are `/#/admin/report` - no server configuration is needed.
+    ///App::start_with_routing_mode("app", init, update, view, RoutingMode::Hash);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the root element cannot be found.
+    pub fn start_with_routing_mode(
+        root_element: impl GetElement,
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+        routing_mode: RoutingMode,
+    ) -> Self {
+        Self::start_with_routing(root_element, init, update, view, Some(routing_mode))
+    }
+
+    /// See `App::start`.
+    ///
+    /// `routing_mode` - listen for back / forward navigation and link clicks and handle
+    /// `subs::UrlRequested`. Embedded apps (e.g. Web Components) shouldn't interfere
+    /// with the page's routing - they pass `None`.
+    pub(crate) fn start_with_routing(
+        root_element: impl GetElement,
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+        routing_mode: Option<RoutingMode>,
     ) -> Self {
         // @TODO: Remove as soon as Webkit is fixed and older browsers are no longer in use.
         // https://github.com/seed-rs/seed/issues/241
@@ -147,21 +213,14 @@ where
         // Allows panic messages to output to the browser console.error.
         console_error_panic_hook::set_once();
 
-        let base_path: Rc<Vec<String>> = Rc::new(
-            util::document()
-                .query_selector("base")
-                .expect("query element with 'base' tag")
-                .and_then(|element| element.get_attribute("href"))
-                .and_then(|href| web_sys::Url::new_with_base(&href, DUMMY_BASE_URL).ok())
-                .map(|url| {
-                    url.pathname()
-                        .trim_matches('/')
-                        .split('/')
-                        .map(ToOwned::to_owned)
-                        .collect()
-                })
-                .unwrap_or_default(),
-        );
+        // Embedded apps (e.g. Web Components) don't touch the page's history.
+        let history = routing_mode.map(|routing_mode| {
+            let history = Rc::new(routing::History::new(routing_mode));
+            history.register();
+            history
+        });
+
+        let (unmount_sender, unmounted) = oneshot::channel();
 
         let app = Self {
             cfg: Rc::new(AppCfg {
@@ -169,56 +228,343 @@ where
                 mount_point: root_element.get_element().expect("get root element"),
                 update: Box::new(move |msg, model, orders| update.clone()(msg, model, orders)),
                 view: Box::new(move |model| view.clone()(model)),
-                base_path,
+                base_path: Rc::new(base_path(routing_mode)),
+                routing_mode,
             }),
             data: Rc::new(AppData {
                 model: RefCell::new(None),
                 root_el: RefCell::new(None),
-                popstate_closure: RefCell::new(None),
-                hashchange_closure: RefCell::new(None),
+                history_listener: RefCell::new(None),
+                link_listener: RefCell::new(None),
+                history: history.clone(),
+                before_unload_closure: RefCell::new(None),
                 window_event_handler_manager: RefCell::new(EventHandlerManager::new()),
                 sub_manager: RefCell::new(SubManager::new()),
                 msg_listeners: RefCell::new(Vec::new()),
+                model_listeners: RefCell::new(Vec::new()),
+                render_scheduler: Cell::new(RenderScheduler::default()),
                 scheduled_render_handle: RefCell::new(None),
                 after_next_render_callbacks: RefCell::new(Vec::new()),
                 render_info: Cell::new(None),
+                rendering: Cell::new(false),
+                render_requested: Cell::new(false),
+                context: Rc::default(),
+                keyed_cmds: Rc::default(),
+                subscriptions: RefCell::new(None),
+                active_subs: RefCell::new(HashMap::new()),
+                navigation_guard: RefCell::new(None),
+                // Memory routing is used mostly by embedded widgets - they shouldn't scroll the page.
+                scroll_restoration: Cell::new(if routing_mode == Some(RoutingMode::Memory) {
+                    ScrollRestoration::disabled()
+                } else {
+                    ScrollRestoration::default()
+                }),
+                time_travel: RefCell::new(None),
+                unmount_sender: RefCell::new(Some(unmount_sender)),
+                unmounted: unmounted.shared(),
             }),
         };
 
-        app.data.root_el.replace(Some(app.bootstrap_vdom()));
-
         let mut orders = OrdersContainer::new(app.clone());
 
-        let new_model = init(
-            Url::current().skip_base_path(&Rc::clone(&app.cfg.base_path)),
-            &mut orders,
-        );
+        let url = history
+            .as_ref()
+            .map_or_else(Url::current, |history| history.current_url());
+        let new_model = routing::History::scope(history.as_ref(), || {
+            init(
+                url.skip_base_path(&Rc::clone(&app.cfg.base_path)),
+                &mut orders,
+            )
+        });
         app.data.model.replace(Some(new_model));
 
-        routing::setup_popstate_listener(
-            enc!((app => s) move |closure| {
-                s.data.popstate_closure.replace(Some(closure));
-            }),
-            enc!((app => s) move |notification| s.notify_with_notification(notification)),
-            Rc::clone(&app.cfg.base_path),
-        );
-        routing::setup_link_listener(
-            enc!((app => s) move |notification| s.notify_with_notification(notification)),
-        );
-
-        orders.subscribe(enc!((app => s) move |url_requested| {
-            routing::url_request_handler(
-                url_requested,
-                Rc::clone(&s.cfg.base_path),
-                move |notification| s.notify_with_notification(notification),
-            )
-        }));
+        if let Some(history) = history {
+            app.setup_routing(history, &mut orders);
+        }
 
         app.process_effect_queue(orders.effects);
         app.rerender_vdom();
         app
     }
 
+    /// Listen for back / forward navigation and link clicks and handle `subs::UrlRequested`.
+    fn setup_routing(
+        &self,
+        history: Rc<routing::History>,
+        orders: &mut OrdersContainer<Ms, Mdl, INodes>,
+    ) {
+        let history_listener = routing::setup_history_listener(
+            enc!((self => s) move |notification| s.notify_with_notification(notification)),
+            enc!((self => s) move |navigation| s.guard_navigation(navigation)),
+            enc!((self => s) move |scroll| s.scroll_after_next_render(scroll)),
+            Rc::clone(&history),
+            Rc::clone(&self.cfg.base_path),
+        );
+        self.data.history_listener.replace(Some(history_listener));
+        self.set_native_scroll_restoration();
+
+        // Apps with memory routing intercept only their own links.
+        let link_target: web_sys::EventTarget = match history.routing_mode() {
+            RoutingMode::Memory => self.cfg.mount_point.clone().into(),
+            RoutingMode::Path | RoutingMode::Hash => util::document().into(),
+        };
+        let link_listener = routing::setup_link_listener(
+            link_target,
+            history.routing_mode(),
+            enc!((self => s) move |notification| s.notify_with_notification(notification)),
+        );
+        self.data.link_listener.replace(Some(link_listener));
+
+        // The lowest priority - other subscribers can handle the request or stop its propagation.
+        orders.subscribe_with_priority(
+            i8::MIN,
+            enc!((self => s) move |url_requested| {
+                routing::url_request_handler(
+                    url_requested,
+                    Rc::clone(&s.cfg.base_path),
+                    |navigation| s.guard_navigation(navigation),
+                    enc!((s) move |scroll| s.scroll_after_next_render(scroll)),
+                    Rc::clone(&history),
+                    enc!((s) move |notification| s.notify_with_notification(notification)),
+                );
+            }),
+        );
+    }
+
+    /// Stop the app and clear the mount point.
+    ///
+    /// It cancels the scheduled render, drops the model (and handles stored in it),
+    /// subscriptions, cmds and streams and detaches the app's window and document listeners.
+    /// Messages sent to the unmounted app are ignored.
+    ///
+    /// Call `App::start` again to remount the app.
+    pub fn unmount(&self) {
+        if self.data.unmount_sender.borrow_mut().take().is_none() {
+            return;
+        }
+        self.cancel_scheduled_render();
+
+        if let Some(history_listener) = self.data.history_listener.borrow_mut().take() {
+            history_listener.remove();
+        }
+        if let Some(link_listener) = self.data.link_listener.borrow_mut().take() {
+            link_listener.remove();
+        }
+        if let Some(history) = &self.data.history {
+            history.unregister();
+        }
+        if let Some(closure) = self.data.before_unload_closure.borrow_mut().take() {
+            routing::remove_before_unload_listener(&closure);
+        }
+
+        // Drop the model first, so `SubHandle`s stored in it unsubscribe normally.
+        let model = self.data.model.borrow_mut().take();
+        drop(model);
+        self.data.sub_manager.borrow_mut().clear();
+        self.data.keyed_cmds.clear();
+        self.data.subscriptions.borrow_mut().take();
+        self.data.active_subs.borrow_mut().clear();
+        self.data.navigation_guard.borrow_mut().take();
+        self.data.after_next_render_callbacks.borrow_mut().clear();
+        self.data.msg_listeners.borrow_mut().clear();
+        self.data.model_listeners.borrow_mut().clear();
+        // It also removes the time-travel panel.
+        let time_travel = self.data.time_travel.borrow_mut().take();
+        drop(time_travel);
+
+        // Dropped elements detach their listeners.
+        if let Some(root_el) = self.data.root_el.borrow_mut().take() {
+            lifecycle_hook::invoke_remove_hooks(&root_el, &self.mailbox());
+            patch::remove_portals(root_el.children, &self.mailbox());
+        }
+        self.cfg.mount_point.set_text_content(None);
+    }
+
+    /// Returns `false` after `unmount`.
+    pub fn is_mounted(&self) -> bool {
+        self.data.unmount_sender.borrow().is_some()
+    }
+
+    /// Attach `stylesheet` to the shadow root the app is mounted into.
+    /// See `Stylesheet` for more info.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the mount point isn't in a shadow root or the stylesheet cannot be attached.
+    pub fn attach_stylesheet(&self, stylesheet: &Stylesheet) -> Result<(), JsValue> {
+        let shadow_root = self
+            .cfg
+            .mount_point
+            .get_root_node()
+            .dyn_into::<web_sys::ShadowRoot>()
+            .map_err(|_| JsValue::from_str("the app isn't mounted into a shadow root"))?;
+        stylesheet.attach(&shadow_root)
+    }
+
+    /// Start the time-travel debugger - a floating panel for stepping through the recorded
+    /// messages and models. See `app::time_travel` for more info.
+    ///
+    /// It's intended for development only - the model is serialized after each `update`.
+    ///
+    /// The debugger runs until the app is unmounted - the returned handle is needed only
+    /// for controlling it from your code.
+    pub fn start_time_travel(&self) -> TimeTravel<Ms, Mdl, INodes>
+    where
+        Ms: fmt::Debug,
+        Mdl: Serialize + DeserializeOwned,
+    {
+        TimeTravel::start(self)
+    }
+
+    /// Move back in the app's history - the browser history or the memory history
+    /// in `RoutingMode::Memory`. See `Url::go_back`.
+    ///
+    /// Apps without routing (e.g. Web Components) move back in the browser history.
+    pub fn go_back(&self, steps: i32) {
+        self.go(-steps);
+    }
+
+    /// Move forward in the app's history - see `App::go_back`.
+    pub fn go_forward(&self, steps: i32) {
+        self.go(steps);
+    }
+
+    fn go(&self, delta: i32) {
+        match &self.data.history {
+            Some(history) => history.go(delta),
+            None => routing::go(delta),
+        }
+    }
+
+    /// Set the function that returns app's declarative subscriptions - see `app::subscriptions`.
+    /// It's called immediately and then after each `update`.
+    pub fn set_subscriptions(&self, subscriptions: impl Fn(&Mdl) -> Subs<Ms> + 'static) {
+        self.data
+            .subscriptions
+            .borrow_mut()
+            .replace(Box::new(subscriptions));
+        self.update_subscriptions();
+    }
+
+    /// Set the guard that can block or confirm in-app link clicks, `Orders::request_url`
+    /// and back or forward navigation. A rejected back or forward navigation returns
+    /// to the history entry the app has left. Return a guard other than `NavigationGuard::Allow`
+    /// for `Navigation::Unload` to trigger the browser's native prompt when the page is left.
+    ///
+    /// Compose guards of your page modules in the same way as their `view`s.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///fn navigation_guard(model: &Model, navigation: &Navigation) -> NavigationGuard {
+    ///    match &model.page {
+    ///        Page::Editor(model) => page::editor::navigation_guard(model, navigation),
+    ///        _ => NavigationGuard::Allow,
+    ///    }
+    ///}
+    ///
+    ///App::start("app", init, update, view).set_navigation_guard(navigation_guard);
+    /// ```
+    pub fn set_navigation_guard(
+        &self,
+        guard: impl Fn(&Mdl, &Navigation) -> NavigationGuard + 'static,
+    ) {
+        self.data
+            .navigation_guard
+            .borrow_mut()
+            .replace(Box::new(guard));
+        if self.data.before_unload_closure.borrow().is_none() {
+            routing::setup_before_unload_listener(
+                enc!((self => s) move |closure| {
+                    s.data.before_unload_closure.replace(Some(closure));
+                }),
+                enc!((self => s) move |navigation| s.guard_navigation(navigation)),
+            );
+        }
+    }
+
+    fn guard_navigation(&self, navigation: &Navigation) -> NavigationGuard {
+        match (
+            self.data.navigation_guard.borrow().as_ref(),
+            self.data.model.borrow().as_ref(),
+        ) {
+            (Some(guard), Some(model)) => guard(model, navigation),
+            _ => NavigationGuard::Allow,
+        }
+    }
+
+    /// Configure scrolling after in-app navigation - see `ScrollRestoration`.
+    pub fn set_scroll_restoration(&self, scroll_restoration: ScrollRestoration) {
+        self.data.scroll_restoration.set(scroll_restoration);
+        if self.data.history_listener.borrow().is_some() {
+            self.set_native_scroll_restoration();
+        }
+    }
+
+    /// The browser would restore the scroll position before the new page is rendered.
+    fn set_native_scroll_restoration(&self) {
+        if self.cfg.routing_mode == Some(RoutingMode::Memory) {
+            return;
+        }
+        let scroll_restoration = if self.data.scroll_restoration.get().restore_on_history {
+            web_sys::ScrollRestoration::Manual
+        } else {
+            web_sys::ScrollRestoration::Auto
+        };
+        // It fails only in very old browsers.
+        let _ = util::history().set_scroll_restoration(scroll_restoration);
+    }
+
+    fn scroll_after_next_render(&self, scroll: Scroll) {
+        if let Some(target) = self.data.scroll_restoration.get().target(scroll) {
+            self.data
+                .after_next_render_callbacks
+                .borrow_mut()
+                .push(Box::new(move |_| {
+                    target.scroll();
+                    None
+                }));
+            // Scroll even when no subscriber handles `UrlChanged`.
+            self.schedule_render();
+        }
+    }
+
+    /// Change when the renders requested by `update` are performed.
+    /// The default is `RenderScheduler::AnimationFrame`. See `RenderScheduler` for more info.
+    ///
+    /// A pending render is rescheduled with the new scheduler.
+    pub fn set_render_scheduler(&self, render_scheduler: RenderScheduler) {
+        self.data.render_scheduler.set(render_scheduler);
+        if self
+            .data
+            .scheduled_render_handle
+            .borrow_mut()
+            .take()
+            .is_some()
+        {
+            self.schedule_render();
+        }
+    }
+
+    /// Perform the scheduled render immediately, if there is any.
+    /// It's the only way to render with `RenderScheduler::Manual`.
+    pub fn flush_render(&self) {
+        if self
+            .data
+            .scheduled_render_handle
+            .borrow_mut()
+            .take()
+            .is_some()
+        {
+            self.rerender_vdom();
+        }
+    }
+
+    /// Returns `true` if a render is scheduled and hasn't been performed yet.
+    pub fn has_scheduled_render(&self) -> bool {
+        self.data.scheduled_render_handle.borrow().is_some()
+    }
+
     /// Invoke your `update` function with provided message.
     pub fn update(&self, message: Ms) {
         self.update_with_option(Some(message));
@@ -246,11 +592,21 @@ where
         self.process_effect_queue(queue);
     }
 
-    pub(crate) fn process_effect_queue(&self, mut queue: VecDeque<Effect<Ms>>) {
+    pub(crate) fn process_effect_queue(&self, queue: VecDeque<Effect<Ms>>) {
+        if !self.is_mounted() {
+            return;
+        }
+        // `Url::go_and_push` & co. called by `update` use the app's history.
+        routing::History::scope(self.data.history.as_ref(), || {
+            self.process_effects(queue);
+        });
+    }
+
+    fn process_effects(&self, mut queue: VecDeque<Effect<Ms>>) {
         while let Some(effect) = queue.pop_front() {
             match effect {
                 Effect::Msg(msg) => {
-                    let mut new_effects = self.process_queue_message(msg);
+                    let mut new_effects = self.process_queue_message(msg, None);
                     queue.append(&mut new_effects);
                 }
                 Effect::Notification(notification) => {
@@ -258,96 +614,69 @@ where
                     queue.append(&mut new_effects);
                 }
                 Effect::TriggeredHandler(handler) => {
-                    let mut new_effects = self.process_queue_message(handler());
+                    let mut new_effects = self.process_queue_message(handler(), None);
+                    queue.append(&mut new_effects);
+                }
+                Effect::NotificationHandler(handler, propagation) => {
+                    let mut new_effects = self.process_queue_message(handler(), Some(propagation));
                     queue.append(&mut new_effects);
                 }
             }
         }
     }
 
-    /// Bootstrap the dom at startup with the vdom by taking over all children of the mount point and
-    /// replacing them with the vdom.
-    fn bootstrap_vdom(&self) -> El<Ms> {
-        // "new" name is for consistency with `update` function.
-        // this section parent is a placeholder, so we can iterate over children
-        // in a way consistent with patching code.
-        let mut new = El::empty(Tag::Placeholder);
-
-        // Map the DOM's elements onto the virtual DOM.
-        // Construct a vdom from the root element. Subsequently strip the workspace so that we
-        // can recreate it later - this is a kind of simple way to avoid missing nodes (but
-        // not entirely correct).
-        // TODO: 1) Please refer to [issue #277](https://github.com/seed-rs/seed/issues/277)
-        let mut dom_nodes: El<Ms> = (&self.cfg.mount_point).into();
-        #[cfg(debug_assertions)]
-        dom_nodes.warn_about_script_tags();
-
-        dom_nodes.strip_ws_nodes_from_self_and_children();
-
-        // Replace the root dom with a placeholder tag and move the children from the root element
-        // to the newly created root. Uses `Placeholder` to mimic update logic.
-        new.children = dom_nodes.children;
-
-        // Recreate the needed nodes.
-        // TODO: Please refer to [issue #277](https://github.com/seed-rs/seed/issues/277)
-        // TODO: Look into how the 0.7 API changes removing of MountType takeover check interact
-        // with the uses of `rerender_vdom` and `bootstrap_vdom`
-        virtual_dom_bridge::assign_ws_nodes_to_el(&util::document(), &mut new);
-
-        // Remove all old elements. We'll swap them out with the newly created elements later.
-        // This maneuver will effectively allow us to remove everything in the mount and thus
-        // takeover the mount point.
-        while let Some(child) = self.cfg.mount_point.first_child() {
-            self.cfg
-                .mount_point
-                .remove_child(&child)
-                .expect("No problem removing node from parent.");
+    /// Render the app and run `after_next_render` callbacks.
+    ///
+    /// A render requested during the current one (e.g. by `update` invoked by
+    /// an `after_next_render` callback with `RenderScheduler::Sync`) isn't performed recursively -
+    /// it's performed after the current render returns.
+    fn rerender_vdom(&self) {
+        if self.data.rendering.replace(true) {
+            self.data.render_requested.set(true);
+            return;
         }
-
-        // Attach all top-level elements to the mount point if present. This means that we have
-        // effectively taken full control of everything within the mounting element.
-        for child in &mut new.children {
-            match child {
-                Node::Element(child_el) => {
-                    virtual_dom_bridge::attach_el_and_children(
-                        child_el,
-                        &self.cfg.mount_point,
-                        &self.mailbox(),
-                    );
-                }
-                Node::Text(top_child_text) => {
-                    virtual_dom_bridge::attach_text_node(top_child_text, &self.cfg.mount_point);
-                }
-                Node::Empty => (),
+        loop {
+            self.data.render_requested.set(false);
+            self.rerender_vdom_once();
+            if !self.data.render_requested.get() {
+                break;
             }
         }
-
-        new
+        self.data.rendering.set(false);
     }
 
-    fn rerender_vdom(&self) {
+    fn rerender_vdom_once(&self) {
+        if !self.is_mounted() {
+            return;
+        }
         let new_render_timestamp = window().performance().expect("get `Performance`").now();
 
         // Create a new vdom: The top element, and all its children. Does not yet
         // have associated web_sys elements.
         let mut new = El::empty(Tag::Placeholder);
-        new.children = (self.cfg.view)(self.data.model.borrow().as_ref().unwrap()).into_nodes();
-
-        let old = self
-            .data
-            .root_el
-            .borrow_mut()
-            .take()
-            .expect("missing root element");
-
-        patch::patch_els(
-            &self.cfg.document,
-            &self.mailbox(),
-            &self.clone(),
-            &self.cfg.mount_point,
-            old.children.into_iter(),
-            new.children.iter_mut(),
-        );
+        let old = self.data.root_el.borrow_mut().take();
+
+        // Lazy nodes are rendered during patching, so they can read the context too.
+        self.data.context.scope(Some(self.context_reader()), || {
+            new.children = (self.cfg.view)(self.data.model.borrow().as_ref().unwrap()).into_nodes();
+
+            match old {
+                Some(old) => patch::patch_els(
+                    &self.cfg.document,
+                    &self.mailbox(),
+                    &self.clone(),
+                    &self.cfg.mount_point,
+                    old.children.into_iter(),
+                    new.children.iter_mut(),
+                ),
+                // The first render - take over the root element's content.
+                None => self.hydrate_vdom(&mut new),
+            }
+        });
+        // Drop the state of components that are no longer rendered.
+        if let Some(instances) = self.data.context.get::<component::Instances>() {
+            instances.retain_rendered();
+        }
 
         // Now that we've re-rendered, replace our stored El with the new one;
         // it will be used as the old El next time.
@@ -377,18 +706,55 @@ where
         );
     }
 
+    /// The app as a context reader - see `app::context`.
+    /// It holds only weak references, so the context store doesn't keep the app alive.
+    fn context_reader(&self) -> Reader {
+        let (cfg, data) = (Rc::downgrade(&self.cfg), Rc::downgrade(&self.data));
+        Reader {
+            id: Rc::as_ptr(&self.data) as *const () as usize,
+            render: Rc::new(move || {
+                if let (Some(cfg), Some(data)) = (cfg.upgrade(), data.upgrade()) {
+                    Self { cfg, data }.schedule_render();
+                }
+            }),
+        }
+    }
+
+    /// Hydrate the mount point's content with the first vdom - reuse existing DOM nodes
+    /// (e.g. from a prerendered page), attach listeners to them and patch only the differences.
+    fn hydrate_vdom(&self, new: &mut El<Ms>) {
+        #[cfg(debug_assertions)]
+        El::<Ms>::from(&self.cfg.mount_point).warn_about_script_tags();
+
+        virtual_dom_bridge::hydrate_children(
+            &self.cfg.document,
+            &self.cfg.mount_point,
+            &mut new.children,
+            &self.mailbox(),
+        );
+    }
+
     fn process_queue_notification(&self, notification: &Notification) -> VecDeque<Effect<Ms>> {
+        // Handlers are processed one by one, so `update` can stop the propagation
+        // before the next handler is invoked.
+        let propagation = Propagation::default();
         self.data
             .sub_manager
             .borrow()
-            .notify(notification)
+            .notify(notification, &propagation)
             .into_iter()
-            .map(Effect::TriggeredHandler)
+            .map(|handler| Effect::NotificationHandler(handler, propagation.clone()))
             .collect()
     }
 
-    fn process_queue_message(&self, message: Option<Ms>) -> VecDeque<Effect<Ms>> {
+    /// `propagation` is `Some` when the `message` has been created by a notification handler.
+    fn process_queue_message(
+        &self,
+        message: Option<Ms>,
+        propagation: Option<Propagation>,
+    ) -> VecDeque<Effect<Ms>> {
         let mut orders = OrdersContainer::new(self.clone());
+        orders.propagation = propagation;
 
         if let Some(message) = message {
             for l in self.data.msg_listeners.borrow().iter() {
@@ -400,6 +766,12 @@ where
                 &mut self.data.model.borrow_mut().as_mut().unwrap(),
                 &mut orders,
             );
+
+            for l in self.data.model_listeners.borrow().iter() {
+                (l)(self.data.model.borrow().as_ref().unwrap())
+            }
+
+            self.update_subscriptions();
         }
 
         match orders.should_render {
@@ -413,27 +785,83 @@ where
         orders.effects
     }
 
+    /// Start new subscriptions returned by `subscriptions` and stop the missing ones.
+    fn update_subscriptions(&self) {
+        let subs = match (
+            self.data.subscriptions.borrow().as_ref(),
+            self.data.model.borrow().as_ref(),
+        ) {
+            (Some(subscriptions), Some(model)) => subscriptions(model),
+            _ => return,
+        };
+        subscriptions::diff(
+            &mut self.data.active_subs.borrow_mut(),
+            subs,
+            |sub| match sub {
+                Sub::Stream(create_stream) => ActiveSub::Stream(
+                    OrdersContainer::new(self.clone()).stream_mapped_with_handle(create_stream()),
+                ),
+                Sub::Notification(type_id, handler) => ActiveSub::Notification(
+                    self.data
+                        .sub_manager
+                        .borrow_mut()
+                        .subscribe_erased_with_handle(type_id, handler, i8::default()),
+                ),
+            },
+        );
+    }
+
     fn schedule_render(&self) {
-        let mut scheduled_render_handle = self.data.scheduled_render_handle.borrow_mut();
+        let render_scheduler = self.data.render_scheduler.get();
+
+        if render_scheduler == RenderScheduler::Sync {
+            self.cancel_scheduled_render();
+            return self.rerender_vdom();
+        }
 
-        if scheduled_render_handle.is_none() {
-            let cb = Closure::new(enclose!((self => s) move |_| {
+        if self.data.scheduled_render_handle.borrow().is_none() {
+            let scheduled_render = render_scheduler.schedule(enclose!((self => s) move || {
                 s.data.scheduled_render_handle.borrow_mut().take();
                 s.rerender_vdom();
             }));
-
-            *scheduled_render_handle = Some(util::request_animation_frame(cb));
+            self.data
+                .scheduled_render_handle
+                .replace(Some(scheduled_render));
         }
     }
 
     fn cancel_scheduled_render(&self) {
-        // Cancel animation frame request by dropping it.
+        // Cancel the render request by dropping it.
         self.data.scheduled_render_handle.borrow_mut().take();
     }
 
     pub fn mailbox(&self) -> Mailbox<Ms> {
+        let history = self.data.history.clone();
         Mailbox::new(enclose!((self => s) move |option_message| {
             s.update_with_option(option_message);
         }))
+        // Event handlers use the app's history too - see `process_effect_queue`.
+        .with_scope(move |handler| routing::History::scope(history.as_ref(), handler))
+    }
+}
+
+/// Path of the `<base>` element's `href`. It's relevant only for `RoutingMode::Path` -
+/// embedded apps without routing use it too.
+fn base_path(routing_mode: Option<RoutingMode>) -> Vec<String> {
+    if matches!(routing_mode, Some(routing_mode) if routing_mode != RoutingMode::Path) {
+        return Vec::new();
     }
+    util::document()
+        .query_selector("base")
+        .expect("query element with 'base' tag")
+        .and_then(|element| element.get_attribute("href"))
+        .and_then(|href| web_sys::Url::new_with_base(&href, DUMMY_BASE_URL).ok())
+        .map(|url| {
+            url.pathname()
+                .trim_matches('/')
+                .split('/')
+                .map(ToOwned::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
 }
@@ -1,4 +1,5 @@
 use super::OrdersContainer;
+use crate::browser::RoutingMode;
 use crate::virtual_dom::IntoNodes;
 use std::rc::Rc;
 
@@ -14,4 +15,6 @@ where
     pub(crate) update: Box<dyn Fn(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>)>,
     pub(crate) view: Box<dyn Fn(&Mdl) -> INodes>,
     pub(crate) base_path: Rc<Vec<String>>,
+    /// `None` for embedded apps without routing - see `App::start_with_routing`.
+    pub(crate) routing_mode: Option<RoutingMode>,
 }
@@ -1,4 +1,6 @@
-use futures::future::{abortable, AbortHandle, Future, FutureExt};
+use futures::future::{self, abortable, AbortHandle, Either, Future, FutureExt};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use wasm_bindgen_futures::spawn_local;
 
 // ------ CmdManager ------
@@ -18,15 +20,97 @@ impl CmdManager {
         spawn_local(cmd.map(move |_| ()));
         CmdHandle(handle)
     }
+
+    /// Resolves to `None` if `stop` resolves before `cmd`. `cmd` is dropped in such case.
+    pub fn until<T>(
+        cmd: impl Future<Output = T> + 'static,
+        stop: impl Future + Unpin,
+    ) -> impl Future<Output = Option<T>> {
+        future::select(cmd.boxed_local(), stop).map(|either| match either {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        })
+    }
 }
 
 // ------ CmdHandle ------
 
 #[derive(Debug)]
-pub struct CmdHandle(AbortHandle);
+pub struct CmdHandle(pub(crate) AbortHandle);
 
 impl Drop for CmdHandle {
     fn drop(&mut self) {
         self.0.abort();
     }
 }
+
+// ------ KeyedCmds ------
+
+/// Handles of cmds started by `Orders::perform_cmd_keyed` and `perform_cmd_debounced`.
+/// Only the latest cmd for each key is running.
+#[derive(Default)]
+pub(crate) struct KeyedCmds {
+    handles: RefCell<HashMap<String, (u64, CmdHandle)>>,
+    next_id: Cell<u64>,
+}
+
+impl KeyedCmds {
+    /// Reserve an id for a new cmd - pass it to `remove_finished` and `replace`.
+    pub fn next_id(&self) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    /// Store the handle of the new cmd. The previous cmd with the same key is aborted.
+    pub fn replace(&self, key: String, id: u64, handle: CmdHandle) {
+        let previous = self.handles.borrow_mut().insert(key, (id, handle));
+        // Drop (i.e. abort) the previous cmd after the borrow is released.
+        drop(previous);
+    }
+
+    /// Remove the handle of the finished cmd, unless it has been already replaced.
+    pub fn remove_finished(&self, key: &str, id: u64) {
+        let mut handles = self.handles.borrow_mut();
+        if handles.get(key).map(|(stored_id, _)| *stored_id) == Some(id) {
+            let removed = handles.remove(key);
+            drop(handles);
+            drop(removed);
+        }
+    }
+
+    /// Abort all running cmds.
+    pub fn clear(&self) {
+        let handles = self.handles.take();
+        drop(handles);
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.handles.borrow().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::Orders;
+    use crate::testing::TestOrders;
+    use futures::executor::block_on;
+    use futures::future::{self, join_all};
+
+    #[test]
+    fn keyed_cmds() {
+        let mut orders = TestOrders::<u8>::new();
+        orders
+            .perform_cmd_keyed("counter", future::ready(1))
+            .perform_cmd_debounced("counter", 300, future::ready(2))
+            .perform_cmd_keyed("other", future::ready(3));
+        assert_eq!(orders.keyed_cmds.len(), 2);
+        assert_eq!(
+            block_on(join_all(orders.take_cmds())),
+            vec![None, Some(2), Some(3)]
+        );
+        // Finished cmds remove their handles.
+        assert_eq!(orders.keyed_cmds.len(), 0);
+    }
+}
@@ -0,0 +1,390 @@
+//! Stateful components with their own state and `Msg`.
+//!
+//! A component's state is owned by the app that renders it - you don't have to store it in your
+//! `Model` or call the component's `init` and `view` functions:
+//!
+//! - The state is created by `Component::init` when the parent's `view` renders the component
+//!   key for the first time.
+//! - It's kept across renders while the parent's `view` renders the same key.
+//! - It's dropped after a render of the parent that doesn't contain the key.
+//!
+//! Component messages go through the parent's `update` like messages of other modules -
+//! map them by `map_msg` in the `view` and pass them to the component by `ComponentMsg::update`
+//! with proxied `orders` (see `Orders::proxy`). Cmds, streams and subscriptions requested
+//! by the component are the parent's ones - store their handles in the component state
+//! to cancel them together with the state.
+//!
+//! _Note:_ Components are rendered during the parent's `view`. Don't render them inside `lazy`
+//! nodes - the state is dropped when the lazy node isn't rerendered.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! struct Counter {
+//!     value: i32,
+//! }
+//!
+//! enum CounterMsg {
+//!     Increment,
+//! }
+//!
+//! impl Component for Counter {
+//!     type Props = String;
+//!     type Msg = CounterMsg;
+//!
+//!     fn init(_: &Self::Props) -> Self {
+//!         Self { value: 0 }
+//!     }
+//!
+//!     fn update(&mut self, msg: Self::Msg, _: &Self::Props, _: &mut impl Orders<Self::Msg>) {
+//!         match msg {
+//!             CounterMsg::Increment => self.value += 1,
+//!         }
+//!     }
+//!
+//!     fn view(&self, label: &Self::Props) -> Node<Self::Msg> {
+//!         button![
+//!             format!("{}: {}", label, self.value),
+//!             ev(Ev::Click, |_| CounterMsg::Increment),
+//!         ]
+//!     }
+//! }
+//!
+//! enum Msg {
+//!     Counter(ComponentMsg<Counter>),
+//! }
+//!
+//! fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+//!     match msg {
+//!         Msg::Counter(msg) => msg.update(&mut orders.proxy(Msg::Counter)),
+//!     }
+//! }
+//!
+//! fn view(model: &Model) -> Node<Msg> {
+//!     div![
+//!         component::<Counter>("clicks", "Clicks".to_owned()).map_msg(Msg::Counter),
+//!         component::<Counter>("other_clicks", model.other_label.clone()).map_msg(Msg::Counter),
+//!     ]
+//! }
+//! ```
+
+use super::{context::ContextStore, MessageMapper, Orders};
+use crate::virtual_dom::Node;
+use std::{any::Any, cell::RefCell, collections::HashMap, fmt, mem, rc::Rc};
+
+// ------ Component ------
+
+/// Component with its own state (`Self`) and messages - see the module documentation.
+pub trait Component: Sized + 'static {
+    /// Data passed from the parent's `view`. They are replaced on each render of the parent.
+    type Props: 'static;
+    type Msg: 'static;
+
+    /// Create the component state. It's called when the component key is rendered
+    /// for the first time.
+    fn init(props: &Self::Props) -> Self;
+
+    fn update(&mut self, msg: Self::Msg, props: &Self::Props, orders: &mut impl Orders<Self::Msg>);
+
+    fn view(&self, props: &Self::Props) -> Node<Self::Msg>;
+}
+
+/// Render the component `C` - see the module documentation.
+///
+/// `key` identifies the component state among all components `C` rendered by the app -
+/// use a unique key for each instance.
+pub fn component<C: Component>(key: impl ToString, props: C::Props) -> Node<ComponentMsg<C>> {
+    let key = instance_key::<C>(&key.to_string());
+    let node = match ContextStore::current() {
+        Some(context) => Instances::of(&context).view::<C>(&key, props),
+        // Rendered outside of an app (e.g. by `Node::to_string`) - there is nothing to keep the state.
+        None => ComponentState::<C>::new(props).view(),
+    };
+    node.map_msg(move |msg| ComponentMsg { key, msg })
+}
+
+fn instance_key<C: Component>(key: &str) -> String {
+    format!("{}:{}", std::any::type_name::<C>(), key)
+}
+
+// ------ ComponentMsg ------
+
+/// Message of the component `C` rendered by `component` - see the module documentation.
+pub struct ComponentMsg<C: Component> {
+    key: String,
+    msg: C::Msg,
+}
+
+impl<C: Component> ComponentMsg<C> {
+    /// Pass the message to the component state. Messages of dropped components are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// Msg::Counter(msg) => msg.update(&mut orders.proxy(Msg::Counter)),
+    /// ```
+    pub fn update(self, orders: &mut impl Orders<Self>) {
+        let Self { key, msg } = self;
+        if let Some(instances) = orders.context::<Instances>() {
+            let instance_key = key.clone();
+            let mut orders = orders.proxy(move |msg| Self { key, msg });
+            instances.update::<C>(&instance_key, msg, &mut orders);
+        }
+    }
+}
+
+// @TODO remove custom impls once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<C: Component> Clone for ComponentMsg<C>
+where
+    C::Msg: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            msg: self.msg.clone(),
+        }
+    }
+}
+
+impl<C: Component> PartialEq for ComponentMsg<C>
+where
+    C::Msg: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.msg == other.msg
+    }
+}
+
+impl<C: Component> fmt::Debug for ComponentMsg<C>
+where
+    C::Msg: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ComponentMsg")
+            .field("key", &self.key)
+            .field("msg", &self.msg)
+            .finish()
+    }
+}
+
+// ------ ComponentState ------
+
+struct ComponentState<C: Component> {
+    component: C,
+    props: C::Props,
+}
+
+impl<C: Component> ComponentState<C> {
+    fn new(props: C::Props) -> Self {
+        Self {
+            component: C::init(&props),
+            props,
+        }
+    }
+
+    fn set_props(&mut self, props: C::Props) {
+        self.props = props;
+    }
+
+    fn update(&mut self, msg: C::Msg, orders: &mut impl Orders<C::Msg>) {
+        self.component.update(msg, &self.props, orders);
+    }
+
+    fn view(&self) -> Node<C::Msg> {
+        self.component.view(&self.props)
+    }
+}
+
+// ------ Instances ------
+
+/// Component states of the app, stored in its context (see `AppData::context`).
+#[derive(Default)]
+pub(crate) struct Instances {
+    instances: RefCell<HashMap<String, Instance>>,
+}
+
+struct Instance {
+    state: Box<dyn Any>,
+    /// The instance has been rendered since the last `retain_rendered` call.
+    rendered: bool,
+}
+
+impl Instances {
+    fn of(context: &ContextStore) -> Rc<Self> {
+        context.get::<Self>().unwrap_or_else(|| {
+            context.insert(Self::default());
+            context
+                .get::<Self>()
+                .expect("get inserted component instances")
+        })
+    }
+
+    /// Render the state of the instance `key`. The state is created when it doesn't exist yet.
+    fn view<C: Component>(&self, key: &str, props: C::Props) -> Node<C::Msg> {
+        // The instance is taken out while rendering, because its `view` may render
+        // other components.
+        let previous = self.instances.borrow_mut().remove(key);
+        let state = match previous.and_then(|instance| instance.state.downcast().ok()) {
+            Some(mut state) => {
+                ComponentState::<C>::set_props(&mut state, props);
+                state
+            }
+            None => Box::new(ComponentState::<C>::new(props)),
+        };
+        let node = state.view();
+        self.instances.borrow_mut().insert(
+            key.to_owned(),
+            Instance {
+                state,
+                rendered: true,
+            },
+        );
+        node
+    }
+
+    fn update<C: Component>(&self, key: &str, msg: C::Msg, orders: &mut impl Orders<C::Msg>) {
+        let mut instance = match self.instances.borrow_mut().remove(key) {
+            Some(instance) => instance,
+            None => return,
+        };
+        if let Some(state) = instance.state.downcast_mut::<ComponentState<C>>() {
+            state.update(msg, orders);
+        }
+        self.instances.borrow_mut().insert(key.to_owned(), instance);
+    }
+
+    /// Drop the instances that haven't been rendered since the last call.
+    pub fn retain_rendered(&self) {
+        self.instances
+            .borrow_mut()
+            .retain(|_, instance| mem::replace(&mut instance.rendered, false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::TestOrders;
+
+    struct Counter(i32);
+
+    #[derive(Debug, PartialEq)]
+    enum CounterMsg {
+        Increment,
+        IncrementLater,
+        IncrementTwice,
+    }
+
+    impl Component for Counter {
+        /// The step.
+        type Props = i32;
+        type Msg = CounterMsg;
+
+        fn init(_: &i32) -> Self {
+            Self(0)
+        }
+
+        fn update(&mut self, msg: CounterMsg, step: &i32, orders: &mut impl Orders<CounterMsg>) {
+            match msg {
+                CounterMsg::Increment => self.0 += step,
+                CounterMsg::IncrementLater => {
+                    orders.perform_cmd(async { CounterMsg::Increment });
+                }
+                CounterMsg::IncrementTwice => {
+                    orders
+                        .send_msg(CounterMsg::Increment)
+                        .send_msg(CounterMsg::Increment);
+                }
+            }
+        }
+
+        fn view(&self, step: &i32) -> Node<CounterMsg> {
+            div![format!("{} (+{})", self.0, step)]
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Msg {
+        Counter(ComponentMsg<Counter>),
+    }
+
+    fn update(msg: Msg, orders: &mut TestOrders<Msg>) {
+        match msg {
+            Msg::Counter(msg) => msg.update(&mut orders.proxy(Msg::Counter)),
+        }
+    }
+
+    /// Render the counters like `App::rerender_vdom`.
+    fn render(orders: &TestOrders<Msg>, counters: &[(&str, i32)]) -> String {
+        let html = orders.context.scope(None, || {
+            div![counters
+                .iter()
+                .map(|(key, step)| component::<Counter>(key, *step).map_msg(Msg::Counter))]
+            .to_string()
+        });
+        Instances::of(&orders.context).retain_rendered();
+        html
+    }
+
+    fn counter_msg(key: &str, msg: CounterMsg) -> Msg {
+        Msg::Counter(ComponentMsg {
+            key: instance_key::<Counter>(key),
+            msg,
+        })
+    }
+
+    #[test]
+    fn state_is_kept_by_key_and_updated_through_parent() {
+        let mut orders = TestOrders::new();
+        assert_eq!(
+            render(&orders, &[("a", 1), ("b", 2)]),
+            "<div><div>0 (+1)</div><div>0 (+2)</div></div>"
+        );
+
+        update(counter_msg("a", CounterMsg::Increment), &mut orders);
+        update(counter_msg("b", CounterMsg::IncrementLater), &mut orders);
+        update(counter_msg("b", CounterMsg::IncrementTwice), &mut orders);
+        assert_eq!(orders.cmd_count(), 1);
+        assert_eq!(
+            *orders.msgs(),
+            [
+                counter_msg("b", CounterMsg::Increment),
+                counter_msg("b", CounterMsg::Increment),
+            ]
+        );
+
+        // New props are used by the next `update`.
+        assert_eq!(
+            render(&orders, &[("b", 5), ("a", 1)]),
+            "<div><div>0 (+5)</div><div>1 (+1)</div></div>"
+        );
+        update(counter_msg("b", CounterMsg::Increment), &mut orders);
+        assert_eq!(
+            render(&orders, &[("a", 1), ("b", 5)]),
+            "<div><div>1 (+1)</div><div>5 (+5)</div></div>"
+        );
+    }
+
+    #[test]
+    fn state_is_dropped_when_key_is_not_rendered() {
+        let mut orders = TestOrders::new();
+        render(&orders, &[("a", 1), ("b", 1)]);
+        update(counter_msg("a", CounterMsg::Increment), &mut orders);
+        update(counter_msg("b", CounterMsg::Increment), &mut orders);
+
+        render(&orders, &[("b", 1)]);
+        // Messages of dropped components are ignored.
+        update(counter_msg("a", CounterMsg::Increment), &mut orders);
+        assert_eq!(
+            render(&orders, &[("a", 1), ("b", 1)]),
+            "<div><div>0 (+1)</div><div>1 (+1)</div></div>"
+        );
+    }
+
+    #[test]
+    fn component_without_app_renders_initial_state() {
+        let node: Node<Msg> = component::<Counter>("a", 3).map_msg(Msg::Counter);
+        assert_eq!(node.to_string(), "<div>0 (+3)</div>");
+    }
+}
@@ -0,0 +1,179 @@
+//! App-level values shared by all modules without passing them through `view`s and `update`s
+//! (a session, translations, a theme, ..).
+//!
+//! - Insert or replace values by `Orders::provide_context` (e.g. in `init`).
+//! - Read them by `Orders::context` in `update` or by `context` in `view`.
+//! - Apps that have read a value in their `view` (including their components' `view`s)
+//!   are rerendered when the value is replaced.
+//!
+//! Values are identified by their type - use newtypes for values of the same type.
+//! Components (see `app::component`) read the context of the app that renders them.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+//!     orders.provide_context(Theme::Dark);
+//!     Model::default()
+//! }
+//!
+//! fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+//!     match msg {
+//!         Msg::ToggleTheme => {
+//!             let theme = orders.context::<Theme>().map_or(Theme::Dark, |theme| theme.toggled());
+//!             orders.provide_context(theme);
+//!         }
+//!     }
+//! }
+//!
+//! // A view deep in the page hierarchy.
+//! fn view_button(label: &str) -> Node<Msg> {
+//!     let theme = context::<Theme>().expect("theme context");
+//!     button![C![theme.class()], label]
+//! }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen_futures::spawn_local;
+
+thread_local! {
+    /// The context of the app whose `view` is being rendered.
+    static CURRENT: RefCell<Option<(Rc<ContextStore>, Option<Reader>)>> = const { RefCell::new(None) };
+}
+
+/// Read the context value of the type `T` in your `view` - see the module documentation.
+///
+/// Returns `None` when the value hasn't been provided or when it's called outside of `view`.
+pub fn context<T: 'static>() -> Option<Rc<T>> {
+    CURRENT.with(|current| {
+        current.borrow().as_ref().and_then(|(store, reader)| {
+            if let Some(reader) = reader {
+                store.add_reader::<T>(reader);
+            }
+            store.get::<T>()
+        })
+    })
+}
+
+// ------ Reader ------
+
+/// An app that has read a context value. `render` schedules the app's render.
+#[derive(Clone)]
+pub(crate) struct Reader {
+    pub id: usize,
+    pub render: Rc<dyn Fn()>,
+}
+
+// ------ ContextStore ------
+
+#[derive(Default)]
+pub(crate) struct ContextStore {
+    values: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
+    readers: RefCell<HashMap<TypeId, Vec<Reader>>>,
+}
+
+impl ContextStore {
+    /// The store of the app that is being rendered (see `scope`).
+    pub fn current() -> Option<Rc<Self>> {
+        CURRENT.with(|current| current.borrow().as_ref().map(|(store, _)| Rc::clone(store)))
+    }
+
+    /// Make the store `current` while `f` is running.
+    /// `reader` is registered for all values read by `context`.
+    pub fn scope<R>(self: &Rc<Self>, reader: Option<Reader>, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.with(|current| current.replace(Some((Rc::clone(self), reader))));
+        let output = f();
+        CURRENT.with(|current| current.replace(previous));
+        output
+    }
+
+    pub fn get<T: 'static>(&self) -> Option<Rc<T>> {
+        self.values
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    /// Insert or replace the value and schedule renders of its readers.
+    pub fn insert<T: 'static>(&self, value: T) {
+        let type_id = TypeId::of::<T>();
+        self.values.borrow_mut().insert(type_id, Rc::new(value));
+
+        // Readers register themselves again during their render.
+        let readers = self
+            .readers
+            .borrow_mut()
+            .remove(&type_id)
+            .unwrap_or_default();
+        if !readers.is_empty() {
+            // Don't render while the caller's `update` is still running.
+            spawn_local(async move {
+                for reader in readers {
+                    (reader.render)();
+                }
+            });
+        }
+    }
+
+    fn add_reader<T: 'static>(&self, reader: &Reader) {
+        let mut readers = self.readers.borrow_mut();
+        let readers = readers.entry(TypeId::of::<T>()).or_default();
+        if readers.iter().all(|registered| registered.id != reader.id) {
+            readers.push(reader.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Orders;
+    use crate::testing::TestOrders;
+
+    #[derive(Debug, PartialEq)]
+    struct Theme(&'static str);
+
+    #[test]
+    fn values_are_read_by_type() {
+        let store = Rc::new(ContextStore::default());
+        assert!(store.get::<Theme>().is_none());
+
+        store.insert(Theme("dark"));
+        store.insert(5_u8);
+        assert_eq!(*store.get::<Theme>().unwrap(), Theme("dark"));
+        assert_eq!(*store.get::<u8>().unwrap(), 5);
+
+        let reader = Reader {
+            id: 1,
+            render: Rc::new(|| ()),
+        };
+        let theme = store.scope(Some(reader.clone()), || {
+            assert!(Rc::ptr_eq(&ContextStore::current().unwrap(), &store));
+            context::<Theme>().map(|theme| theme.0)
+        });
+        assert_eq!(theme, Some("dark"));
+        assert!(context::<Theme>().is_none());
+        assert!(ContextStore::current().is_none());
+
+        store.scope(Some(reader), context::<Theme>);
+        assert_eq!(store.readers.borrow()[&TypeId::of::<Theme>()].len(), 1);
+    }
+
+    #[test]
+    fn provide_and_read_context() {
+        let mut orders = TestOrders::<Option<u8>>::new();
+        assert!(orders.context::<String>().is_none());
+
+        orders.proxy(Some).provide_context("dark".to_owned());
+        orders.provide_context(1_u8).provide_context(2_u8);
+
+        assert_eq!(
+            orders.context::<String>().as_deref(),
+            Some(&"dark".to_owned())
+        );
+        assert_eq!(orders.proxy(Some).context::<u8>().as_deref(), Some(&2));
+    }
+}
@@ -1,7 +1,19 @@
-use super::{RenderInfo, SubManager};
-use crate::browser::util;
+use super::{
+    cmd_manager::KeyedCmds,
+    context::ContextStore,
+    render_scheduler::ScheduledRender,
+    scroll_restoration::ScrollRestoration,
+    subscriptions::{ActiveSub, Subs},
+    Navigation, NavigationGuard, RenderInfo, RenderScheduler, SubManager,
+};
+use crate::browser::service::routing;
 use crate::virtual_dom::{El, EventHandlerManager};
+use futures::channel::oneshot;
+use futures::future::Shared;
+use std::any::Any;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use wasm_bindgen::closure::Closure;
 
 type StoredPopstate = RefCell<Option<Closure<dyn FnMut(web_sys::Event)>>>;
@@ -10,12 +22,41 @@ type StoredPopstate = RefCell<Option<Closure<dyn FnMut(web_sys::Event)>>>;
 pub(crate) struct AppData<Ms: 'static, Mdl> {
     pub model: RefCell<Option<Mdl>>,
     pub(crate) root_el: RefCell<Option<El<Ms>>>,
-    pub popstate_closure: StoredPopstate,
-    pub hashchange_closure: StoredPopstate,
+    /// Back and forward navigation listener - see `RoutingMode`.
+    pub history_listener: RefCell<Option<routing::HistoryListener>>,
+    pub link_listener: RefCell<Option<routing::Listener>>,
+    /// History entries visited by the app - `None` for apps without routing.
+    pub history: Option<Rc<routing::History>>,
+    pub before_unload_closure: StoredPopstate,
     pub window_event_handler_manager: RefCell<EventHandlerManager<Ms>>,
     pub sub_manager: RefCell<SubManager<Ms>>,
     pub msg_listeners: RefCell<Vec<Box<dyn Fn(&Ms)>>>,
-    pub scheduled_render_handle: RefCell<Option<util::RequestAnimationFrameHandle>>,
+    /// Invoked with the updated model after each `update`.
+    pub model_listeners: RefCell<Vec<Box<dyn Fn(&Mdl)>>>,
+    pub render_scheduler: Cell<RenderScheduler>,
+    pub scheduled_render_handle: RefCell<Option<ScheduledRender>>,
     pub after_next_render_callbacks: RefCell<Vec<Box<dyn FnOnce(RenderInfo) -> Option<Ms>>>>,
     pub render_info: Cell<Option<RenderInfo>>,
+    /// `true` while `App::rerender_vdom` is running.
+    pub rendering: Cell<bool>,
+    /// A render has been requested during the current render.
+    pub render_requested: Cell<bool>,
+    /// Values provided by `Orders::provide_context` and the app's component states
+    /// (see `app::component`).
+    pub context: Rc<ContextStore>,
+    /// Cmds started by `Orders::perform_cmd_keyed` and `perform_cmd_debounced`.
+    pub keyed_cmds: Rc<KeyedCmds>,
+    /// See `App::set_subscriptions`.
+    pub subscriptions: RefCell<Option<Box<dyn Fn(&Mdl) -> Subs<Ms>>>>,
+    pub active_subs: RefCell<HashMap<String, ActiveSub>>,
+    /// See `App::set_navigation_guard`.
+    pub navigation_guard: RefCell<Option<Box<dyn Fn(&Mdl, &Navigation) -> NavigationGuard>>>,
+    /// See `App::set_scroll_restoration`.
+    pub scroll_restoration: Cell<ScrollRestoration>,
+    /// Keeps the debugger started by `App::start_time_travel` running.
+    pub time_travel: RefCell<Option<Rc<dyn Any>>>,
+    /// Dropped by `App::unmount`.
+    pub unmount_sender: RefCell<Option<oneshot::Sender<()>>>,
+    /// Resolves on unmount - it stops all cmds and streams started by the app.
+    pub unmounted: Shared<oneshot::Receiver<()>>,
 }
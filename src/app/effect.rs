@@ -1,9 +1,12 @@
+use super::sub_manager::Propagation;
 use super::{MessageMapper, Notification};
 
 pub enum Effect<Ms> {
     Msg(Option<Ms>),
     Notification(Notification),
     TriggeredHandler(Box<dyn FnOnce() -> Option<Ms>>),
+    /// Subscription handler invoked by a notification.
+    NotificationHandler(Box<dyn FnOnce() -> Option<Ms>>, Propagation),
 }
 
 impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Effect<Ms> {
@@ -15,6 +18,9 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Effect<Ms> {
             Effect::TriggeredHandler(handler) => {
                 Effect::TriggeredHandler(Box::new(move || handler().map(f)))
             }
+            Effect::NotificationHandler(handler, propagation) => {
+                Effect::NotificationHandler(Box::new(move || handler().map(f)), propagation)
+            }
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::browser::util::document;
-use web_sys::{Element, HtmlElement};
+use web_sys::{Element, HtmlElement, ShadowRoot};
 
 pub trait GetElement {
     /// Returns wrapped `web_sys::Element` or tries to get one from the DOM.
@@ -29,3 +29,25 @@ impl GetElement for HtmlElement {
         Ok(self.into())
     }
 }
+
+/// Returns the root's mount point - a `div` with the attribute `data-seed-root`.
+/// It's created and appended to the root if it doesn't exist yet.
+impl GetElement for ShadowRoot {
+    fn get_element(self) -> Result<Element, String> {
+        let children = self.children();
+        let mount_point = (0..children.length())
+            .filter_map(|index| children.item(index))
+            .find(|child| child.has_attribute("data-seed-root"));
+        if let Some(mount_point) = mount_point {
+            return Ok(mount_point);
+        }
+        let mount_point = document()
+            .create_element("div")
+            .map_err(|error| format!("cannot create shadow root's mount point: {:?}", error))?;
+        mount_point
+            .set_attribute("data-seed-root", "")
+            .and_then(|_| self.append_child(&mount_point))
+            .map_err(|error| format!("cannot append shadow root's mount point: {:?}", error))?;
+        Ok(mount_point)
+    }
+}
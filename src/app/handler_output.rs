@@ -0,0 +1,66 @@
+// ------ HandlerOutput ------
+
+/// Values that can be returned from event handlers, cmds, streams, subscription handlers
+/// and other callbacks - `Msg`, `Option<Msg>` or `()`.
+///
+/// Other values don't compile. The trait is sealed - it can't be implemented outside of Seed.
+///
+/// `Kind` distinguishes the implementations, it's inferred by the compiler.
+/// _Note:_ When your `Msg` is `()` and the handler returns `()`, the compiler can't infer it;
+/// return `Some(())` or `None` instead.
+pub trait HandlerOutput<Ms, Kind>: sealed::Sealed<Ms, Kind> {
+    /// `None` means "no message" - `update` won't be called.
+    fn into_msg(self) -> Option<Ms>;
+}
+
+impl<Ms> HandlerOutput<Ms, kind::Msg> for Ms {
+    fn into_msg(self) -> Option<Ms> {
+        Some(self)
+    }
+}
+
+impl<Ms> HandlerOutput<Ms, kind::OptionMsg> for Option<Ms> {
+    fn into_msg(self) -> Option<Ms> {
+        self
+    }
+}
+
+impl<Ms> HandlerOutput<Ms, kind::Unit> for () {
+    fn into_msg(self) -> Option<Ms> {
+        None
+    }
+}
+
+/// Markers for `HandlerOutput` implementations.
+pub mod kind {
+    pub enum Msg {}
+    pub enum OptionMsg {}
+    pub enum Unit {}
+}
+
+mod sealed {
+    use super::kind;
+
+    pub trait Sealed<Ms, Kind> {}
+
+    impl<Ms> Sealed<Ms, kind::Msg> for Ms {}
+    impl<Ms> Sealed<Ms, kind::OptionMsg> for Option<Ms> {}
+    impl<Ms> Sealed<Ms, kind::Unit> for () {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn into_msg<MsU: HandlerOutput<u8, Kind>, Kind>(output: MsU) -> Option<u8> {
+        output.into_msg()
+    }
+
+    #[test]
+    fn outputs_are_converted_to_option_msg() {
+        assert_eq!(into_msg(5), Some(5));
+        assert_eq!(into_msg(Some(5)), Some(5));
+        assert_eq!(into_msg(None), None);
+        assert_eq!(into_msg(()), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! Transfer of the initial state from the server to the client.
+//!
+//! The server serializes a model (or its part) into a `<script type="application/json">` tag
+//! alongside the prerendered `view` and the client reads it back in `init`.
+//! So `init` can rebuild the model without the second round trip and flash of empty content.
+//!
+//! _Note:_ Place the script tag outside of the mount point, otherwise it will be removed
+//! during hydration.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! // Server
+//! let html = format!(
+//!     r#"<div id="app">{}</div>{}"#,
+//!     render_to_string(view(&model)),
+//!     initial_state::to_script("app-state", &model.user)?,
+//! );
+//!
+//! // Client
+//! fn init(_: Url, _: &mut impl Orders<Msg>) -> Model {
+//!     Model {
+//!         user: initial_state::load("app-state").ok(),
+//!     }
+//! }
+//! ```
+
+use super::GetElement;
+use crate::virtual_dom::{to_html::escape_attr, El, Node, Tag};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Convenient type alias.
+pub type Result<T> = std::result::Result<T, InitialStateError>;
+
+// ------ InitialStateError ------
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum InitialStateError {
+    ElementNotFound(String),
+    MissingContent,
+    SerdeError(serde_json::Error),
+}
+
+// ------ Server ------
+
+/// Serialize `state` into `<script type="application/json" id="{id}">` as an HTML string.
+///
+/// # Errors
+///
+/// Returns error if `state` cannot be serialized.
+pub fn to_script(id: &str, state: &impl Serialize) -> Result<String> {
+    Ok(format!(
+        r#"<script type="application/json" id="{}">{}</script>"#,
+        escape_attr(id),
+        to_script_content(state)?
+    ))
+}
+
+/// Serialize `state` into a `<script type="application/json" id="{id}">` node.
+/// Useful when you render the whole page, including the script tag, from `Node`s.
+///
+/// # Errors
+///
+/// Returns error if `state` cannot be serialized.
+pub fn to_node<Ms>(id: &str, state: &impl Serialize) -> Result<Node<Ms>> {
+    let mut script = El::empty(Tag::from("script"));
+    script
+        .add_attr("type", "application/json")
+        .add_attr("id", id.to_owned())
+        .add_text(to_script_content(state)?);
+    Ok(Node::Element(script))
+}
+
+/// Serialize `state` to JSON that is safe to be used as a raw script content.
+fn to_script_content(state: &impl Serialize) -> Result<String> {
+    let json = serde_json::to_string(state).map_err(InitialStateError::SerdeError)?;
+    // `<` would allow to close the script tag (`</script>`) or open a comment (`<!--`)
+    // and `&` an HTML entity. JSON strings can contain their escaped versions instead.
+    Ok(json.replace('<', "\\u003c").replace('&', "\\u0026"))
+}
+
+// ------ Client ------
+
+/// Deserialize the state embedded by `to_script` or `to_node`.
+///
+/// # Errors
+///
+/// Returns error if the script element cannot be found or its content cannot be deserialized.
+pub fn load<T: DeserializeOwned>(script_element: impl GetElement) -> Result<T> {
+    let content = script_element
+        .get_element()
+        .map_err(InitialStateError::ElementNotFound)?
+        .text_content()
+        .ok_or(InitialStateError::MissingContent)?;
+    serde_json::from_str(&content).map_err(InitialStateError::SerdeError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct User {
+        name: String,
+    }
+
+    #[test]
+    fn script() {
+        let user = User {
+            name: "</script><script>alert('&')</script>".to_owned(),
+        };
+        assert_eq!(
+            to_script(r#"state"1"#, &user).unwrap(),
+            concat!(
+                r#"<script type="application/json" id="state&quot;1">"#,
+                r#"{"name":"\u003c/script>\u003cscript>alert('\u0026')\u003c/script>"}"#,
+                "</script>"
+            )
+        );
+    }
+
+    #[test]
+    fn node_roundtrip() {
+        let mut state = BTreeMap::new();
+        state.insert("<!--", vec![1, 2]);
+
+        let node = to_node::<()>("state", &state).unwrap();
+        let html = node.to_string();
+        assert_eq!(
+            html,
+            r#"<script type="application/json" id="state">{"\u003c!--":[1,2]}</script>"#
+        );
+
+        let content = node.el().unwrap().get_text();
+        let deserialized: BTreeMap<String, Vec<i32>> = serde_json::from_str(&content).unwrap();
+        assert_eq!(deserialized["<!--"], vec![1, 2]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::browser::{util::window, Url};
+use futures::future::{FutureExt, LocalBoxFuture};
+use std::future::Future;
+
+// ------ Navigation ------
+
+/// Navigation passed to the app's navigation guard - see `App::set_navigation_guard`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Navigation {
+    /// In-app link click or `Orders::request_url`.
+    Request(Url),
+    /// Back or forward navigation (`popstate`).
+    History(Url),
+    /// The page is being unloaded (reload, tab closing, external link).
+    /// Any guard except `NavigationGuard::Allow` triggers the browser's native prompt.
+    Unload,
+}
+
+impl Navigation {
+    /// The requested `Url` (without the base path). Returns `None` for `Navigation::Unload`.
+    pub const fn url(&self) -> Option<&Url> {
+        match self {
+            Self::Request(url) | Self::History(url) => Some(url),
+            Self::Unload => None,
+        }
+    }
+}
+
+// ------ NavigationGuard ------
+
+/// Decision of the app's navigation guard - see `App::set_navigation_guard`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///fn navigation_guard(model: &Model, navigation: &Navigation) -> NavigationGuard {
+///    if model.saved {
+///        NavigationGuard::Allow
+///    } else {
+///        NavigationGuard::Confirm("Do you want to leave? Data won't be saved.".to_owned())
+///    }
+///}
+/// ```
+#[derive(Default)]
+pub enum NavigationGuard {
+    #[default]
+    Allow,
+    Block,
+    /// Ask the user with the native confirmation dialog.
+    Confirm(String),
+    /// Navigate when the future resolves to `true` - e.g. after a custom modal dialog is closed.
+    /// Create it by `NavigationGuard::confirm_with`.
+    ConfirmWith(LocalBoxFuture<'static, bool>),
+}
+
+impl NavigationGuard {
+    pub fn confirm_with(confirmation: impl Future<Output = bool> + 'static) -> Self {
+        Self::ConfirmWith(confirmation.boxed_local())
+    }
+
+    /// Returns `Ok(allowed)` for synchronous guards, `Confirm` shows the confirmation dialog.
+    ///
+    /// # Errors
+    ///
+    /// Returns the confirmation future of `ConfirmWith`.
+    pub(crate) fn resolve(self) -> Result<bool, LocalBoxFuture<'static, bool>> {
+        match self {
+            Self::Allow => Ok(true),
+            Self::Block => Ok(false),
+            Self::Confirm(message) => {
+                Ok(window().confirm_with_message(&message).unwrap_or_default())
+            }
+            Self::ConfirmWith(confirmation) => Err(confirmation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn resolve_guards() {
+        assert_eq!(NavigationGuard::Allow.resolve().ok(), Some(true));
+        assert_eq!(NavigationGuard::Block.resolve().ok(), Some(false));
+        let confirmation = NavigationGuard::confirm_with(async { true })
+            .resolve()
+            .expect_err("asynchronous guard");
+        assert!(block_on(confirmation));
+    }
+
+    #[test]
+    fn navigation_url() {
+        let url = Url::new().set_path(["admin"]);
+        assert_eq!(Navigation::Request(url.clone()).url(), Some(&url));
+        assert_eq!(Navigation::History(url.clone()).url(), Some(&url));
+        assert_eq!(Navigation::Unload.url(), None);
+    }
+}
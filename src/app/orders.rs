@@ -1,8 +1,13 @@
-use super::{subs, App, CmdHandle, RenderInfo, StreamHandle, SubHandle};
+use super::{
+    cmd_manager::KeyedCmds, context::ContextStore, sub_manager::SubManager, subs, App, CmdHandle,
+    HandlerOutput, Notification, RenderInfo, ShouldRender, StreamHandle, SubHandle,
+};
 use crate::browser::Url;
 use crate::virtual_dom::IntoNodes;
-use futures::stream::Stream;
-use std::{any::Any, future::Future, rc::Rc};
+use futures::future::{FutureExt, LocalBoxFuture};
+use futures::stream::{LocalBoxStream, Stream};
+use serde::Serialize;
+use std::{any::Any, cell::RefMut, future::Future, rc::Rc};
 
 // @TODO: Add links to doc comment once https://github.com/rust-lang/rust/issues/43466 is resolved
 // or use nightly rustdoc. Applicable to the entire code base.
@@ -74,7 +79,7 @@ pub trait Orders<Ms: 'static> {
 
     /// Execute `cmd` and send its output (if it's `Msg`) to `update` function.
     ///
-    /// Output has to be `Msg`, `Option<Msg>` or `()`.
+    /// Output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     ///
     /// # Example
     ///
@@ -84,22 +89,17 @@ pub trait Orders<Ms: 'static> {
     /// ```
     ///
     /// _Note:_: Use the alternative `perform_cmd_with_handle` to control `cmd`'s lifetime.
-    ///
-    /// # Panics
-    ///
-    /// Panics when the output isn't `Msg`, `Option<Msg>` or `()`.
-    ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
     #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn perform_cmd<MsU: 'static>(&mut self, cmd: impl Future<Output = MsU> + 'static) -> &mut Self;
+    fn perform_cmd<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self;
 
     /// Execute given `cmd` and send its output (if it's `Msg`) to `update` function.
     /// - Returns `CmdHandle` that you should save to your `Model`.
     ///   The `cmd` is aborted on the handle drop.
     ///
-    /// Output has to be `Msg`, `Option<Msg>` or `()`.
+    /// Output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     ///
     /// # Example
     ///
@@ -107,21 +107,56 @@ pub trait Orders<Ms: 'static> {
     ///let timeout_handle = orders.perform_cmd_with_handle(cmds::timeout(2000, || Msg::OnTimeout));
     ///let cmd_handle = orders.perform_cmd_with_handle(async { log!("Hello!") });
     /// ```
-    ///
-    /// # Panics
-    ///
-    /// Panics when the output isn't `Msg`, `Option<Msg>` or `()`.
-    ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
     #[must_use = "cmd is aborted on its handle drop"]
     #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn perform_cmd_with_handle<MsU: 'static>(
+    fn perform_cmd_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         cmd: impl Future<Output = MsU> + 'static,
     ) -> CmdHandle;
 
+    /// Execute given `cmd` and abort the still running `cmd` with the same `key`
+    /// ("switch to latest"). Only the output of the latest `cmd` is sent to `update`.
+    ///
+    /// Keys are shared by all modules of the app - prefix them to prevent collisions.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///orders.perform_cmd_keyed("search", async move { Msg::Searched(search(query).await) });
+    /// ```
+    ///
+    /// The output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    fn perform_cmd_keyed<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self;
+
+    /// The same as `perform_cmd_keyed`, but `cmd` starts after `delay_ms` milliseconds.
+    /// Each call with the same `key` restarts the delay, so only the last `cmd`
+    /// from a burst of calls is executed.
+    ///
+    /// _Note:_ Futures are lazy - create requests inside the `async` block,
+    /// otherwise they are sent immediately.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::QueryChanged(query) => {
+    ///    orders.perform_cmd_debounced("search", 300, async move {
+    ///        Msg::Searched(search(query).await)
+    ///    });
+    ///}
+    /// ```
+    ///
+    /// The output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    fn perform_cmd_debounced<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        delay_ms: u32,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self;
+
     /// Get app instance. Cloning is cheap because `App` contains only `Rc` fields.
     fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes>;
 
@@ -181,27 +216,19 @@ pub trait Orders<Ms: 'static> {
     ///
     /// - It's useful when you want to use DOM API or make animations.
     /// - You can call this function multiple times - callbacks will be executed in the same order.
-    /// - Callback has to return `Msg`, `Option<Msg>` or `()`.
+    /// - Callback has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     ///
     /// _Note:_ [performance.now()](https://developer.mozilla.org/en-US/docs/Web/API/Performance/now)
     ///  is used under the hood to get timestamps.
-    ///
-    /// # Panics
-    ///
-    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-    ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
     #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn after_next_render<MsU: 'static>(
+    fn after_next_render<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         callback: impl FnOnce(RenderInfo) -> MsU + 'static,
     ) -> &mut Self;
 
     /// Subscribe for messages with the `handler`s input type.
     ///
-    /// Handler has to return `Msg`, `Option<Msg>` or `()`.
+    /// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     ///
     /// # Example
     ///
@@ -215,16 +242,8 @@ pub trait Orders<Ms: 'static> {
     /// ```
     ///
     /// _Note:_: Use the alternative `subscribe_with_handle` to control `sub`'s lifetime.
-    ///
-    /// # Panics
-    ///
-    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-    ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
     #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn subscribe<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
     ) -> &mut Self;
@@ -233,7 +252,7 @@ pub trait Orders<Ms: 'static> {
     /// - Returns `SubHandle` that you should save to your `Model`.
     ///   The `sub` is cancelled on the handle drop.
     ///
-    /// Handler has to return `Msg`, `Option<Msg>` or `()`.
+    /// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     ///
     /// # Example
     ///
@@ -245,22 +264,65 @@ pub trait Orders<Ms: 'static> {
     ///orders.notify(counter::DoReset);
     ///orders.notify("Hello!");
     /// ```
+    #[must_use = "subscription is cancelled on its handle drop"]
+    #[allow(clippy::shadow_unrelated)]
+    fn subscribe_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> SubHandle;
+
+    /// The same as `subscribe`, but subscribers with higher `priority` are invoked first.
+    ///
+    /// - The default priority is `0`; subscribers with the same priority are invoked
+    ///   in the subscription order.
+    /// - Seed's handler of `subs::UrlRequested` has the lowest priority (`i8::MIN`).
+    /// - Higher-priority subscribers can stop the delivery - see `stop_propagation`.
+    ///
+    /// # Example
     ///
-    /// # Panics
+    /// ```rust,no_run
+    ///orders.subscribe_with_priority(10, Msg::UrlRequested);
+    /// ```
     ///
-    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+    /// The output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    fn subscribe_with_priority<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        priority: i8,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> &mut Self;
+
+    /// The same as `subscribe_with_handle`, but with the `priority` - see `subscribe_with_priority`.
     ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
+    /// The output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     #[must_use = "subscription is cancelled on its handle drop"]
-    #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn subscribe_with_handle<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_handle_and_priority<
+        MsU: HandlerOutput<Ms, Kind>,
+        Kind,
+        SubMs: 'static + Clone,
+    >(
         &mut self,
+        priority: i8,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
     ) -> SubHandle;
 
-    /// Stream `Msg`, `Option<Msg>` or `()`.
+    /// Mark the notification that has produced the current message as consumed.
+    ///
+    /// Subscribers of a notification are invoked one by one in the priority order
+    /// and each message created by them is handled by `update` before the next subscriber
+    /// is invoked. When `update` calls `stop_propagation`, the remaining subscribers are skipped.
+    /// It has no effect when the current message hasn't been created by a subscriber.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::UrlRequested(subs::UrlRequested(_, url_request)) if model.has_unsaved_changes => {
+    ///    url_request.handled_and_prevent_refresh();
+    ///    orders.stop_propagation();
+    ///}
+    /// ```
+    fn stop_propagation(&mut self) -> &mut Self;
+
+    /// Stream `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     ///
     /// # Example
     ///
@@ -270,18 +332,13 @@ pub trait Orders<Ms: 'static> {
     /// ```
     ///
     /// _Note:_: Use the alternative `stream_with_handle` to control `stream`'s lifetime.
-    ///
-    /// # Panics
-    ///
-    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-    ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
     #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn stream<MsU: 'static>(&mut self, stream: impl Stream<Item = MsU> + 'static) -> &mut Self;
+    fn stream<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> &mut Self;
 
-    /// Stream `Msg`, `Option<Msg>` or `()`.
+    /// Stream `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
     /// - Returns `StreamHandle` that you should save to your `Model`.
     ///   The `stream` is cancelled on the handle drop.
     ///
@@ -291,21 +348,57 @@ pub trait Orders<Ms: 'static> {
     ///let timer_handler = orders.stream_with_handle(streams::interval(1000, || Msg::OnTick));
     ///let stream_handler = orders.stream_with_handle(streams::window_event(Ev::Resize, |_| Msg::OnResize));
     /// ```
-    ///
-    /// # Panics
-    ///
-    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-    ///
-    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
     #[must_use = "stream is stopped on its handle drop"]
     #[allow(clippy::shadow_unrelated)]
-    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
-    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
-    fn stream_with_handle<MsU: 'static>(
+    fn stream_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         stream: impl Stream<Item = MsU> + 'static,
     ) -> StreamHandle;
 
+    /// Dispatch `CustomEvent` with the name `event_name` and serialized `detail`
+    /// on the app's root element once the current `update` has finished.
+    ///
+    /// The event bubbles and it's `composed` - it crosses shadow DOM boundaries,
+    /// so it's dispatched also on the host element when the app is a Web Component
+    /// (see `app::web_component`).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::Increment => {
+    ///    model.counter += 1;
+    ///    orders.dispatch_custom_event("counter-changed", model.counter);
+    ///}
+    /// ```
+    fn dispatch_custom_event(&mut self, event_name: &str, detail: impl Serialize) -> &mut Self;
+
+    /// Insert or replace the context value of the type `T`.
+    /// Apps that have read the previous value in their `view` are rerendered.
+    ///
+    /// See `app::context` for more info.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::LoggedIn(session) => {
+    ///    orders.provide_context(session);
+    ///}
+    /// ```
+    fn provide_context<T: 'static>(&mut self, value: T) -> &mut Self;
+
+    /// Get the context value of the type `T` provided by `provide_context`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::Save => {
+    ///    if let Some(session) = orders.context::<Session>() {
+    ///        orders.perform_cmd(save(session.token.clone(), model.form.clone()));
+    ///    }
+    ///}
+    /// ```
+    fn context<T: 'static>(&self) -> Option<Rc<T>>;
+
     /// Cheap clone base path loaded from element `<base href="/base/path/">`.
     ///
     /// Returns empty `Vec` if there is no `base` element in your HTML
@@ -321,3 +414,79 @@ pub trait Orders<Ms: 'static> {
         self.notify(subs::UrlRequested::new(url))
     }
 }
+
+// ------ MappedOrders ------
+
+/// Orders with messages already mapped to the app's message type.
+///
+/// `OrdersProxy` maps messages of child modules and passes them to its parent orders
+/// through this trait - `OrdersContainer` in a running app or `seed::testing::TestOrders` in tests.
+pub(crate) trait MappedOrders<AppMs: 'static, Mdl: 'static, INodes: IntoNodes<AppMs>> {
+    fn set_should_render(&mut self, should_render: ShouldRender);
+
+    fn send_mapped_msg(&mut self, msg: AppMs);
+
+    fn notify_mapped(&mut self, notification: Notification);
+
+    fn perform_mapped_cmd(&mut self, cmd: LocalBoxFuture<'static, Option<AppMs>>);
+
+    fn perform_mapped_cmd_with_handle(
+        &mut self,
+        cmd: LocalBoxFuture<'static, Option<AppMs>>,
+    ) -> CmdHandle;
+
+    fn keyed_cmds(&self) -> Rc<KeyedCmds>;
+
+    fn perform_mapped_cmd_keyed(
+        &mut self,
+        key: String,
+        cmd: LocalBoxFuture<'static, Option<AppMs>>,
+    ) {
+        let keyed_cmds = self.keyed_cmds();
+        let id = keyed_cmds.next_id();
+        let cmd = {
+            let (keyed_cmds, key) = (Rc::clone(&keyed_cmds), key.clone());
+            cmd.map(move |msg| {
+                keyed_cmds.remove_finished(&key, id);
+                msg
+            })
+        };
+        let handle = self.perform_mapped_cmd_with_handle(cmd.boxed_local());
+        keyed_cmds.replace(key, id, handle);
+    }
+
+    fn perform_mapped_cmd_debounced(
+        &mut self,
+        key: String,
+        delay_ms: u32,
+        cmd: LocalBoxFuture<'static, Option<AppMs>>,
+    );
+
+    fn after_next_render_mapped(&mut self, callback: Box<dyn FnOnce(RenderInfo) -> Option<AppMs>>);
+
+    fn sub_manager(&self) -> RefMut<'_, SubManager<AppMs>>;
+
+    fn stop_mapped_propagation(&mut self);
+
+    fn stream_mapped(&mut self, stream: LocalBoxStream<'static, Option<AppMs>>);
+
+    fn stream_mapped_with_handle(
+        &mut self,
+        stream: LocalBoxStream<'static, Option<AppMs>>,
+    ) -> StreamHandle;
+
+    /// `detail` is `Err` when it couldn't be serialized.
+    fn dispatch_serialized_custom_event(
+        &mut self,
+        event_name: &str,
+        detail: serde_json::Result<serde_json::Value>,
+    );
+
+    fn context_store(&self) -> &ContextStore;
+
+    fn clone_mapped_app(&self) -> App<AppMs, Mdl, INodes>;
+
+    fn mapped_msg_sender(&self) -> Rc<dyn Fn(Option<AppMs>)>;
+
+    fn mapped_base_path(&self) -> Rc<Vec<String>>;
+}
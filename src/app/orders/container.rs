@@ -1,13 +1,21 @@
-use crate::app::cmd_manager::CmdManager;
-use crate::app::orders::{proxy::OrdersProxy, Orders};
+use crate::app::cmd_manager::{CmdManager, KeyedCmds};
+use crate::app::context::ContextStore;
+use crate::app::orders::{proxy::OrdersProxy, MappedOrders, Orders};
 use crate::app::stream_manager::StreamManager;
+use crate::app::sub_manager::{Propagation, SubManager};
 use crate::app::{
-    App, CmdHandle, Effect, Notification, RenderInfo, ShouldRender, StreamHandle, SubHandle,
+    App, CmdHandle, Effect, HandlerOutput, Notification, RenderInfo, ShouldRender, StreamHandle,
+    SubHandle,
 };
 use crate::virtual_dom::IntoNodes;
-use futures::future::FutureExt;
-use futures::stream::{Stream, StreamExt};
-use std::{any::Any, collections::VecDeque, convert::identity, future::Future, rc::Rc};
+use futures::future::{FutureExt, LocalBoxFuture};
+use futures::stream::{LocalBoxStream, Stream, StreamExt};
+use gloo_timers::future::TimeoutFuture;
+use serde::Serialize;
+use std::{
+    any::Any, cell::RefMut, collections::VecDeque, convert::identity, future::Future, rc::Rc,
+};
+use wasm_bindgen::JsValue;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct OrdersContainer<Ms, Mdl, INodes>
@@ -18,6 +26,8 @@ where
 {
     pub(crate) should_render: ShouldRender,
     pub(crate) effects: VecDeque<Effect<Ms>>,
+    /// `Some` when `update` handles a message created by a notification handler.
+    pub(crate) propagation: Option<Propagation>,
     app: App<Ms, Mdl, INodes>,
 }
 
@@ -29,11 +39,145 @@ where
         Self {
             should_render: ShouldRender::Render,
             effects: VecDeque::<Effect<Ms>>::new(),
+            propagation: None,
             app,
         }
     }
 }
 
+// ------ Already mapped to `Ms` ------
+// Shared by `OrdersContainer` and `OrdersProxy`.
+
+impl<Ms, Mdl, INodes> MappedOrders<Ms, Mdl, INodes> for OrdersContainer<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    fn set_should_render(&mut self, should_render: ShouldRender) {
+        self.should_render = should_render;
+    }
+
+    fn send_mapped_msg(&mut self, msg: Ms) {
+        self.effects.push_back(Effect::Msg(Some(msg)));
+    }
+
+    fn notify_mapped(&mut self, notification: Notification) {
+        self.effects.push_back(Effect::Notification(notification));
+    }
+
+    fn perform_mapped_cmd(&mut self, cmd: LocalBoxFuture<'static, Option<Ms>>) {
+        let app = self.app.clone();
+        let cmd = CmdManager::until(cmd, app.data.unmounted.clone());
+        CmdManager::perform_cmd(cmd.map(move |msg| {
+            if let Some(msg) = msg {
+                app.mailbox().send(msg)
+            }
+        }));
+    }
+
+    fn perform_mapped_cmd_with_handle(
+        &mut self,
+        cmd: LocalBoxFuture<'static, Option<Ms>>,
+    ) -> CmdHandle {
+        let app = self.app.clone();
+        let cmd = CmdManager::until(cmd, app.data.unmounted.clone());
+        CmdManager::perform_cmd_with_handle(cmd.map(move |msg| {
+            if let Some(msg) = msg {
+                app.mailbox().send(msg)
+            }
+        }))
+    }
+
+    fn keyed_cmds(&self) -> Rc<KeyedCmds> {
+        Rc::clone(&self.app.data.keyed_cmds)
+    }
+
+    fn perform_mapped_cmd_debounced(
+        &mut self,
+        key: String,
+        delay_ms: u32,
+        cmd: LocalBoxFuture<'static, Option<Ms>>,
+    ) {
+        let cmd = TimeoutFuture::new(delay_ms).then(move |_| cmd);
+        self.perform_mapped_cmd_keyed(key, cmd.boxed_local());
+    }
+
+    fn after_next_render_mapped(&mut self, callback: Box<dyn FnOnce(RenderInfo) -> Option<Ms>>) {
+        self.app
+            .data
+            .after_next_render_callbacks
+            .borrow_mut()
+            .push(callback);
+    }
+
+    fn sub_manager(&self) -> RefMut<'_, SubManager<Ms>> {
+        self.app.data.sub_manager.borrow_mut()
+    }
+
+    fn stop_mapped_propagation(&mut self) {
+        if let Some(propagation) = &self.propagation {
+            propagation.stop();
+        }
+    }
+
+    fn stream_mapped(&mut self, stream: LocalBoxStream<'static, Option<Ms>>) {
+        let app = self.app.clone();
+        let stream = StreamManager::until(stream, app.data.unmounted.clone());
+        StreamManager::stream(stream.map(move |msg| app.mailbox().send(msg)));
+    }
+
+    fn stream_mapped_with_handle(
+        &mut self,
+        stream: LocalBoxStream<'static, Option<Ms>>,
+    ) -> StreamHandle {
+        let app = self.app.clone();
+        let stream = StreamManager::until(stream, app.data.unmounted.clone());
+        StreamManager::stream_with_handle(stream.map(move |msg| app.mailbox().send(msg)))
+    }
+
+    fn dispatch_serialized_custom_event(
+        &mut self,
+        event_name: &str,
+        detail: serde_json::Result<serde_json::Value>,
+    ) {
+        let detail = match detail.and_then(|detail| JsValue::from_serde(&detail)) {
+            Ok(detail) => detail,
+            Err(error) => {
+                crate::error(error);
+                return;
+            }
+        };
+        let (target, event_name) = (self.app.cfg.mount_point.clone(), event_name.to_owned());
+        // Event listeners may send messages to the app - dispatch the event after `update`.
+        CmdManager::perform_cmd(async move {
+            let mut event_init = web_sys::CustomEventInit::new();
+            event_init.bubbles(true).composed(true).detail(&detail);
+            let event = web_sys::CustomEvent::new_with_event_init_dict(&event_name, &event_init)
+                .expect("create `CustomEvent`");
+            target
+                .dispatch_event(&event)
+                .expect("dispatch `CustomEvent`");
+        });
+    }
+
+    fn context_store(&self) -> &ContextStore {
+        &self.app.data.context
+    }
+
+    fn clone_mapped_app(&self) -> App<Ms, Mdl, INodes> {
+        self.app.clone()
+    }
+
+    fn mapped_msg_sender(&self) -> Rc<dyn Fn(Option<Ms>)> {
+        let app = self.app.clone();
+        Rc::new(move |msg| app.update_with_option(msg))
+    }
+
+    fn mapped_base_path(&self) -> Rc<Vec<String>> {
+        Rc::clone(&self.app.cfg.base_path)
+    }
+}
+
 impl<Ms, Mdl, INodes> Orders<Ms> for OrdersContainer<Ms, Mdl, INodes>
 where
     Ms: 'static,
@@ -77,36 +221,50 @@ where
         self
     }
 
-    fn perform_cmd<MsU: 'static>(&mut self, cmd: impl Future<Output = MsU> + 'static) -> &mut Self {
-        let app = self.app.clone();
-
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Cmds can return only Msg, Option<Msg> or ()!",
-            Box
-        );
+    fn perform_cmd<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
 
-        let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg)));
-        CmdManager::perform_cmd(cmd);
+        self.perform_mapped_cmd(cmd.map(handler).boxed_local());
         self
     }
 
-    fn perform_cmd_with_handle<MsU: 'static>(
+    fn perform_cmd_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         cmd: impl Future<Output = MsU> + 'static,
     ) -> CmdHandle {
-        let app = self.app.clone();
+        let handler = |msg: MsU| msg.into_msg();
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Cmds can return only Msg, Option<Msg> or ()!",
-            Box
-        );
+        self.perform_mapped_cmd_with_handle(cmd.map(handler).boxed_local())
+    }
+
+    fn perform_cmd_keyed<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
 
-        let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg)));
-        CmdManager::perform_cmd_with_handle(cmd)
+        self.perform_mapped_cmd_keyed(key.to_string(), cmd.map(handler).boxed_local());
+        self
+    }
+
+    fn perform_cmd_debounced<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        delay_ms: u32,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.perform_mapped_cmd_debounced(
+            key.to_string(),
+            delay_ms,
+            cmd.map(handler).boxed_local(),
+        );
+        self
     }
 
     fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes> {
@@ -117,91 +275,99 @@ where
         Rc::new(identity)
     }
 
-    fn after_next_render<MsU: 'static>(
+    fn after_next_render<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         callback: impl FnOnce(RenderInfo) -> MsU + 'static,
     ) -> &mut Self {
-        let callback = map_callback_return_to_option_ms!(
-            dyn FnOnce(RenderInfo) -> Option<Ms>,
-            callback,
-            "Callback can return only Msg, Option<Msg> or ()!",
-            Box
-        );
+        self.after_next_render_mapped(Box::new(move |render_info| {
+            callback(render_info).into_msg()
+        }));
+        self
+    }
 
-        self.app
-            .data
-            .after_next_render_callbacks
-            .borrow_mut()
-            .push(callback);
+    fn subscribe<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> &mut Self {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        self.sub_manager()
+            .subscribe_with_priority(handler, i8::default());
         self
     }
 
-    fn subscribe<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> SubHandle {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        self.sub_manager()
+            .subscribe_with_handle_and_priority(handler, i8::default())
+    }
+
+    fn subscribe_with_priority<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
         &mut self,
+        priority: i8,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
     ) -> &mut Self {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(SubMs) -> Option<Ms>,
-            handler.clone(),
-            "Handler can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
 
-        self.app
-            .data
-            .sub_manager
-            .borrow_mut()
-            .subscribe(move |sub_ms| handler(sub_ms));
+        self.sub_manager()
+            .subscribe_with_priority(handler, priority);
         self
     }
 
-    fn subscribe_with_handle<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_handle_and_priority<
+        MsU: HandlerOutput<Ms, Kind>,
+        Kind,
+        SubMs: 'static + Clone,
+    >(
         &mut self,
+        priority: i8,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
     ) -> SubHandle {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(SubMs) -> Option<Ms>,
-            handler.clone(),
-            "Handler can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
 
-        self.app
-            .data
-            .sub_manager
-            .borrow_mut()
-            .subscribe_with_handle(move |sub_ms| handler(sub_ms))
+        self.sub_manager()
+            .subscribe_with_handle_and_priority(handler, priority)
     }
 
-    fn stream<MsU: 'static>(&mut self, stream: impl Stream<Item = MsU> + 'static) -> &mut Self {
-        let app = self.app.clone();
+    fn stop_propagation(&mut self) -> &mut Self {
+        self.stop_mapped_propagation();
+        self
+    }
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Streams can stream only Msg, Option<Msg> or ()!",
-            Box
-        );
+    fn stream<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
 
-        let stream = stream.map(move |msg| app.mailbox().send(handler(msg)));
-        StreamManager::stream(stream);
+        self.stream_mapped(stream.map(handler).boxed_local());
         self
     }
 
-    fn stream_with_handle<MsU: 'static>(
+    fn stream_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         stream: impl Stream<Item = MsU> + 'static,
     ) -> StreamHandle {
-        let app = self.app.clone();
+        let handler = |msg: MsU| msg.into_msg();
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Streams can stream only Msg, Option<Msg> or ()!",
-            Box
-        );
+        self.stream_mapped_with_handle(stream.map(handler).boxed_local())
+    }
+
+    fn dispatch_custom_event(&mut self, event_name: &str, detail: impl Serialize) -> &mut Self {
+        self.dispatch_serialized_custom_event(event_name, serde_json::to_value(detail));
+        self
+    }
+
+    fn provide_context<T: 'static>(&mut self, value: T) -> &mut Self {
+        self.context_store().insert(value);
+        self
+    }
 
-        let stream = stream.map(move |msg| app.mailbox().send(handler(msg)));
-        StreamManager::stream_with_handle(stream)
+    fn context<T: 'static>(&self) -> Option<Rc<T>> {
+        self.context_store().get()
     }
 }
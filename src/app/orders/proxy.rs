@@ -1,14 +1,16 @@
 use super::{
-    super::{App, CmdHandle, RenderInfo, StreamHandle, SubHandle},
-    Orders, OrdersContainer,
+    super::{
+        App, CmdHandle, HandlerOutput, Notification, RenderInfo, ShouldRender, StreamHandle,
+        SubHandle,
+    },
+    MappedOrders, Orders, OrdersContainer,
 };
 
-use crate::app::cmd_manager::CmdManager;
-use crate::app::stream_manager::StreamManager;
 use crate::virtual_dom::IntoNodes;
 use futures::future::{Future, FutureExt};
 use futures::stream::{Stream, StreamExt};
-use std::{any::Any, convert::identity, rc::Rc};
+use serde::Serialize;
+use std::{any::Any, rc::Rc};
 
 #[allow(clippy::module_name_repetitions)]
 pub struct OrdersProxy<'a, Ms, AppMs, Mdl, INodes>
@@ -17,7 +19,7 @@ where
     Mdl: 'static,
     INodes: IntoNodes<AppMs>,
 {
-    orders_container: &'a mut OrdersContainer<AppMs, Mdl, INodes>,
+    orders_container: &'a mut dyn MappedOrders<AppMs, Mdl, INodes>,
     f: Rc<dyn Fn(Ms) -> AppMs>,
 }
 
@@ -30,6 +32,17 @@ where
     pub fn new(
         orders_container: &'a mut OrdersContainer<AppMs, Mdl, INodes>,
         f: impl Fn(Ms) -> AppMs + 'static,
+    ) -> Self
+    where
+        INodes: 'static,
+    {
+        Self::new_mapped(orders_container, f)
+    }
+
+    /// Create a proxy of other orders than `OrdersContainer` (e.g. `seed::testing::TestOrders`).
+    pub(crate) fn new_mapped(
+        orders_container: &'a mut dyn MappedOrders<AppMs, Mdl, INodes>,
+        f: impl Fn(Ms) -> AppMs + 'static,
     ) -> Self {
         OrdersProxy {
             orders_container,
@@ -60,69 +73,98 @@ where
     }
 
     fn render(&mut self) -> &mut Self {
-        self.orders_container.render();
+        self.orders_container
+            .set_should_render(ShouldRender::Render);
         self
     }
 
     fn force_render_now(&mut self) -> &mut Self {
-        self.orders_container.force_render_now();
+        self.orders_container
+            .set_should_render(ShouldRender::ForceRenderNow);
         self
     }
 
     fn skip(&mut self) -> &mut Self {
-        self.orders_container.skip();
+        self.orders_container.set_should_render(ShouldRender::Skip);
         self
     }
 
     fn notify(&mut self, message: impl Any + Clone) -> &mut Self {
-        self.orders_container.notify(message);
+        self.orders_container
+            .notify_mapped(Notification::new(message));
         self
     }
 
     #[allow(clippy::redundant_closure)]
     fn send_msg(&mut self, msg: Ms) -> &mut Self {
         let f = self.f.clone();
-        self.orders_container.send_msg(f(msg));
+        self.orders_container.send_mapped_msg(f(msg));
         self
     }
 
     #[allow(clippy::redundant_closure)]
-    fn perform_cmd<MsU: 'static>(&mut self, cmd: impl Future<Output = MsU> + 'static) -> &mut Self {
+    fn perform_cmd<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
         let f = self.f.clone();
-        let app = self.clone_app();
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Cmds can return only Msg, Option<Msg> or ()!",
-            Box
-        );
+        let handler = |msg: MsU| msg.into_msg();
 
-        let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        CmdManager::perform_cmd(cmd);
+        let cmd = cmd.map(move |msg| handler(msg).map(|msg| f(msg)));
+        self.orders_container.perform_mapped_cmd(cmd.boxed_local());
         self
     }
 
-    fn perform_cmd_with_handle<MsU: 'static>(
+    fn perform_cmd_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         cmd: impl Future<Output = MsU> + 'static,
     ) -> CmdHandle {
         let f = self.f.clone();
-        let app = self.clone_app();
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Cmds can return only Msg, Option<Msg> or ()!",
-            Box
-        );
+        let handler = |msg: MsU| msg.into_msg();
 
-        let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        CmdManager::perform_cmd_with_handle(cmd)
+        let cmd = cmd.map(move |msg| handler(msg).map(|msg| f(msg)));
+        self.orders_container
+            .perform_mapped_cmd_with_handle(cmd.boxed_local())
+    }
+
+    fn perform_cmd_keyed<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let f = self.f.clone();
+
+        let handler = |msg: MsU| msg.into_msg();
+
+        let cmd = cmd.map(move |msg| handler(msg).map(|msg| f(msg)));
+        self.orders_container
+            .perform_mapped_cmd_keyed(key.to_string(), cmd.boxed_local());
+        self
+    }
+
+    fn perform_cmd_debounced<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        delay_ms: u32,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let f = self.f.clone();
+
+        let handler = |msg: MsU| msg.into_msg();
+
+        let cmd = cmd.map(move |msg| handler(msg).map(|msg| f(msg)));
+        self.orders_container.perform_mapped_cmd_debounced(
+            key.to_string(),
+            delay_ms,
+            cmd.boxed_local(),
+        );
+        self
     }
 
     fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes> {
-        self.orders_container.clone_app()
+        self.orders_container.clone_mapped_app()
     }
 
     #[allow(clippy::redundant_closure)]
@@ -131,98 +173,135 @@ where
         Rc::new(move |ms| f(ms))
     }
 
-    fn after_next_render<MsU: 'static>(
+    fn after_next_render<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         callback: impl FnOnce(RenderInfo) -> MsU + 'static,
     ) -> &mut Self {
-        let callback = map_callback_return_to_option_ms!(
-            dyn FnOnce(RenderInfo) -> Option<Ms>,
-            callback,
-            "Callback can return only Msg, Option<Msg> or ()!",
-            Box
-        );
+        let callback = move |value: RenderInfo| callback(value).into_msg();
 
         let f = self.f.clone();
-        self.clone_app()
-            .data
-            .after_next_render_callbacks
-            .borrow_mut()
-            .push(Box::new(move |render_info| {
+        self.orders_container
+            .after_next_render_mapped(Box::new(move |render_info| {
                 callback(render_info).map(|ms| f(ms))
             }));
         self
     }
 
-    fn subscribe<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
     ) -> &mut Self {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(SubMs) -> Option<Ms>,
-            handler.clone(),
-            "Handler can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
 
         let f = self.f.clone();
-        self.clone_app()
-            .data
-            .sub_manager
-            .borrow_mut()
-            .subscribe(move |sub_ms| handler(sub_ms).map(|ms| f(ms)));
+        self.orders_container
+            .sub_manager()
+            .subscribe_with_priority(move |sub_ms| handler(sub_ms).map(|ms| f(ms)), i8::default());
         self
     }
 
-    fn subscribe_with_handle<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
     ) -> SubHandle {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(SubMs) -> Option<Ms>,
-            handler.clone(),
-            "Handler can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        let f = self.f.clone();
+        self.orders_container
+            .sub_manager()
+            .subscribe_with_handle_and_priority(
+                move |sub_ms| handler(sub_ms).map(|ms| f(ms)),
+                i8::default(),
+            )
+    }
+
+    fn subscribe_with_priority<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        priority: i8,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> &mut Self {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
 
         let f = self.f.clone();
-        self.clone_app()
-            .data
-            .sub_manager
-            .borrow_mut()
-            .subscribe_with_handle(move |sub_ms| handler(sub_ms).map(|ms| f(ms)))
+        self.orders_container
+            .sub_manager()
+            .subscribe_with_priority(move |sub_ms| handler(sub_ms).map(|ms| f(ms)), priority);
+        self
     }
 
-    fn stream<MsU: 'static>(&mut self, stream: impl Stream<Item = MsU> + 'static) -> &mut Self {
+    fn subscribe_with_handle_and_priority<
+        MsU: HandlerOutput<Ms, Kind>,
+        Kind,
+        SubMs: 'static + Clone,
+    >(
+        &mut self,
+        priority: i8,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> SubHandle {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
         let f = self.f.clone();
-        let app = self.clone_app();
+        self.orders_container
+            .sub_manager()
+            .subscribe_with_handle_and_priority(
+                move |sub_ms| handler(sub_ms).map(|ms| f(ms)),
+                priority,
+            )
+    }
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Streams can stream only Msg, Option<Msg> or ()!",
-            Box
-        );
+    fn stop_propagation(&mut self) -> &mut Self {
+        self.orders_container.stop_mapped_propagation();
+        self
+    }
 
-        let stream = stream.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        StreamManager::stream(stream);
+    fn stream<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> &mut Self {
+        let f = self.f.clone();
+
+        let handler = |msg: MsU| msg.into_msg();
+
+        let stream = stream.map(move |msg| handler(msg).map(|msg| f(msg)));
+        self.orders_container.stream_mapped(stream.boxed_local());
         self
     }
 
-    fn stream_with_handle<MsU: 'static>(
+    fn stream_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
         &mut self,
         stream: impl Stream<Item = MsU> + 'static,
     ) -> StreamHandle {
         let f = self.f.clone();
-        let app = self.clone_app();
 
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MsU) -> Option<Ms>,
-            identity,
-            "Streams can stream only Msg, Option<Msg> or ()!",
-            Box
-        );
+        let handler = |msg: MsU| msg.into_msg();
+
+        let stream = stream.map(move |msg| handler(msg).map(|msg| f(msg)));
+        self.orders_container
+            .stream_mapped_with_handle(stream.boxed_local())
+    }
+
+    fn provide_context<T: 'static>(&mut self, value: T) -> &mut Self {
+        self.orders_container.context_store().insert(value);
+        self
+    }
+
+    fn context<T: 'static>(&self) -> Option<Rc<T>> {
+        self.orders_container.context_store().get()
+    }
+
+    fn dispatch_custom_event(&mut self, event_name: &str, detail: impl Serialize) -> &mut Self {
+        self.orders_container
+            .dispatch_serialized_custom_event(event_name, serde_json::to_value(detail));
+        self
+    }
+
+    #[allow(clippy::redundant_closure)]
+    fn msg_sender(&self) -> Rc<dyn Fn(Option<Ms>)> {
+        let (msg_sender, f) = (self.orders_container.mapped_msg_sender(), self.f.clone());
+        Rc::new(move |msg| msg_sender(msg.map(|msg| f(msg))))
+    }
 
-        let stream = stream.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        StreamManager::stream_with_handle(stream)
+    fn clone_base_path(&self) -> Rc<Vec<String>> {
+        self.orders_container.mapped_base_path()
     }
 }
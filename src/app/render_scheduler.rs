@@ -0,0 +1,123 @@
+use crate::browser::util::{self, window};
+use futures::future::{abortable, AbortHandle, FutureExt};
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+
+// ------ RenderScheduler ------
+
+/// Determines when the render requested by `update` (see `ShouldRender::Render`) is performed.
+/// Multiple requests are coalesced into one render.
+///
+/// Set it by `App::set_render_scheduler`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let app = App::start("app", init, update, view);
+/// // Render also in background tabs.
+/// app.set_render_scheduler(RenderScheduler::IdleCallback { timeout: Some(100) });
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RenderScheduler {
+    /// Render in the next animation frame (default).
+    /// _Note:_ Browsers don't call animation frames in background tabs.
+    #[default]
+    AnimationFrame,
+    /// Render in a microtask - after the current JS task, before the browser paints.
+    /// It also coalesces messages sent synchronously (e.g. a burst of websocket messages
+    /// processed in one task).
+    Microtask,
+    /// Render when the browser is idle (`requestIdleCallback`).
+    /// The render is forced after `timeout` milliseconds, if set.
+    /// Browsers without `requestIdleCallback` (e.g. Safari) render in the next task (`setTimeout`).
+    IdleCallback { timeout: Option<u32> },
+    /// Render immediately after each `update` - the same as `ShouldRender::ForceRenderNow`.
+    /// A render requested during another render (e.g. by an `after_next_render` callback)
+    /// is performed after that render.
+    Sync,
+    /// Don't render until `App::flush_render` is called. Useful for tests.
+    Manual,
+}
+
+impl RenderScheduler {
+    /// Schedule `render`. Dropping the returned handle cancels the render.
+    ///
+    /// # Panics
+    ///
+    /// Panics on `RenderScheduler::Sync` - there is nothing to schedule.
+    pub(crate) fn schedule(self, render: impl FnOnce() + 'static) -> ScheduledRender {
+        let mut render = Some(render);
+        let mut render = move || {
+            if let Some(render) = render.take() {
+                render();
+            }
+        };
+        match self {
+            Self::AnimationFrame => ScheduledRender::AnimationFrame {
+                _handle: util::request_animation_frame(Closure::wrap(
+                    Box::new(move |_| render()) as Box<dyn FnMut(f64)>
+                )),
+            },
+            Self::Microtask => {
+                let (render, handle) = abortable(async move { render() });
+                // Ignore the error when the future is aborted.
+                spawn_local(render.map(|_| ()));
+                ScheduledRender::Microtask(handle)
+            }
+            Self::IdleCallback { .. } if !idle_callback_supported() => ScheduledRender::Timeout {
+                _timeout: Timeout::new(0, render),
+            },
+            Self::IdleCallback { timeout } => {
+                let closure = Closure::wrap(Box::new(move |_| render()) as Box<dyn FnMut(f64)>);
+                let mut options = web_sys::IdleRequestOptions::new();
+                if let Some(timeout) = timeout {
+                    options.timeout(timeout);
+                }
+                let handle = window()
+                    .request_idle_callback_with_options(closure.as_ref().unchecked_ref(), &options)
+                    .expect("Problem requesting idle callback");
+                ScheduledRender::IdleCallback {
+                    handle,
+                    _closure: closure,
+                }
+            }
+            Self::Manual => ScheduledRender::Manual,
+            Self::Sync => panic!("synchronous render can't be scheduled"),
+        }
+    }
+}
+
+fn idle_callback_supported() -> bool {
+    js_sys::Reflect::has(&window(), &JsValue::from_str("requestIdleCallback")).unwrap_or_default()
+}
+
+// ------ ScheduledRender ------
+
+/// Pending render. It's cancelled on drop.
+pub(crate) enum ScheduledRender {
+    AnimationFrame {
+        _handle: util::RequestAnimationFrameHandle,
+    },
+    Microtask(AbortHandle),
+    IdleCallback {
+        handle: u32,
+        _closure: Closure<dyn FnMut(f64)>,
+    },
+    /// Fallback for `IdleCallback`.
+    Timeout {
+        _timeout: Timeout,
+    },
+    Manual,
+}
+
+impl Drop for ScheduledRender {
+    fn drop(&mut self) {
+        match self {
+            Self::Microtask(handle) => handle.abort(),
+            Self::IdleCallback { handle, .. } => window().cancel_idle_callback(*handle),
+            // Handles cancel their requests on drop.
+            Self::AnimationFrame { .. } | Self::Timeout { .. } | Self::Manual => (),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::browser::util::{document, window};
+use wasm_bindgen::JsCast;
+
+// ------ ScrollRestoration ------
+
+/// Scrolling after in-app navigation (link clicks, `Orders::request_url`, back and forward).
+/// The page is scrolled after the next render, so the new page content is already in the DOM.
+///
+/// Set it by `App::set_scroll_restoration`. All behaviors are enabled by default.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let app = App::start("app", init, update, view);
+/// // Keep the scroll position when a link is clicked.
+/// app.set_scroll_restoration(ScrollRestoration {
+///     top_on_push: false,
+///     ..ScrollRestoration::default()
+/// });
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScrollRestoration {
+    /// Restore the scroll position of the history entry on back or forward navigation.
+    /// The browser's native scroll restoration is disabled when it's enabled.
+    pub restore_on_history: bool,
+    /// Scroll to top when a new history entry is pushed.
+    pub top_on_push: bool,
+    /// Scroll to the element whose `id` or `name` matches the url hash (`#fragment`).
+    pub anchors: bool,
+}
+
+impl Default for ScrollRestoration {
+    fn default() -> Self {
+        Self {
+            restore_on_history: true,
+            top_on_push: true,
+            anchors: true,
+        }
+    }
+}
+
+impl ScrollRestoration {
+    /// Seed doesn't scroll after navigation.
+    pub const fn disabled() -> Self {
+        Self {
+            restore_on_history: false,
+            top_on_push: false,
+            anchors: false,
+        }
+    }
+
+    pub(crate) fn target(self, scroll: Scroll) -> Option<ScrollTarget> {
+        match scroll {
+            Scroll::Push { hash: Some(hash) } if self.anchors => Some(ScrollTarget::Anchor(hash)),
+            Scroll::Push { .. } if self.top_on_push => Some(ScrollTarget::Top),
+            Scroll::History {
+                position: Some((x, y)),
+                ..
+            } if self.restore_on_history => Some(ScrollTarget::Position(x, y)),
+            Scroll::History {
+                hash: Some(hash), ..
+            } if self.anchors => Some(ScrollTarget::Anchor(hash)),
+            Scroll::Push { .. } | Scroll::History { .. } => None,
+        }
+    }
+}
+
+// ------ Scroll ------
+
+/// Navigation that may scroll the page.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Scroll {
+    Push {
+        hash: Option<String>,
+    },
+    History {
+        /// Saved when the history entry has been left.
+        position: Option<(f64, f64)>,
+        hash: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ScrollTarget {
+    Position(f64, f64),
+    Top,
+    /// Falls back to `Top` when there is no matching element (e.g. with hash routing).
+    Anchor(String),
+}
+
+impl ScrollTarget {
+    pub(crate) fn scroll(self) {
+        let (x, y) = match self {
+            Self::Position(x, y) => (x, y),
+            Self::Top => (0., 0.),
+            Self::Anchor(hash) => {
+                let element = document().get_element_by_id(&hash).or_else(|| {
+                    document()
+                        .get_elements_by_name(&hash)
+                        .get(0)
+                        .and_then(|node| node.dyn_into::<web_sys::Element>().ok())
+                });
+                if let Some(element) = element {
+                    element.scroll_into_view();
+                    return;
+                }
+                (0., 0.)
+            }
+        };
+        window().scroll_to_with_x_and_y(x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(hash: Option<&str>) -> Scroll {
+        Scroll::Push {
+            hash: hash.map(ToOwned::to_owned),
+        }
+    }
+
+    fn history(position: Option<(f64, f64)>, hash: Option<&str>) -> Scroll {
+        Scroll::History {
+            position,
+            hash: hash.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn default_targets() {
+        let cfg = ScrollRestoration::default();
+        assert_eq!(cfg.target(push(None)), Some(ScrollTarget::Top));
+        assert_eq!(
+            cfg.target(push(Some("intro"))),
+            Some(ScrollTarget::Anchor("intro".to_owned()))
+        );
+        assert_eq!(
+            cfg.target(history(Some((0., 250.)), Some("intro"))),
+            Some(ScrollTarget::Position(0., 250.))
+        );
+        assert_eq!(
+            cfg.target(history(None, Some("intro"))),
+            Some(ScrollTarget::Anchor("intro".to_owned()))
+        );
+        assert_eq!(cfg.target(history(None, None)), None);
+    }
+
+    #[test]
+    fn configured_targets() {
+        let cfg = ScrollRestoration {
+            anchors: false,
+            ..ScrollRestoration::default()
+        };
+        assert_eq!(cfg.target(push(Some("intro"))), Some(ScrollTarget::Top));
+        assert_eq!(cfg.target(history(None, Some("intro"))), None);
+
+        let cfg = ScrollRestoration::disabled();
+        assert_eq!(cfg.target(push(None)), None);
+        assert_eq!(cfg.target(history(Some((0., 250.)), None)), None);
+    }
+}
@@ -1,5 +1,5 @@
-use futures::future::{abortable, ready, AbortHandle, FutureExt};
-use futures::stream::{Stream, StreamExt};
+use futures::future::{abortable, ready, AbortHandle, Future, FutureExt};
+use futures::stream::{self, Stream, StreamExt};
 use wasm_bindgen_futures::spawn_local;
 
 // ------ StreamManager ------
@@ -21,12 +21,23 @@ impl StreamManager {
         spawn_local(stream.map(move |_| ()));
         StreamHandle(handle)
     }
+
+    /// End `stream` when `stop` resolves.
+    pub fn until<T>(stream: impl Stream<Item = T>, stop: impl Future) -> impl Stream<Item = T> {
+        // `None` from any side ends the stream.
+        stream::select(
+            stop.into_stream().map(|_| None),
+            stream.map(Some).chain(ready(None).into_stream()),
+        )
+        .take_while(|item| ready(item.is_some()))
+        .map(|item| item.expect("stream item"))
+    }
 }
 
 // ------ StreamHandle ------
 
 #[derive(Debug)]
-pub struct StreamHandle(AbortHandle);
+pub struct StreamHandle(pub(crate) AbortHandle);
 
 impl Drop for StreamHandle {
     fn drop(&mut self) {
@@ -1,8 +1,13 @@
+use crate::app::RenderInfo;
 use crate::browser::util::{document, window};
-use crate::virtual_dom::Ev;
-use futures::stream::{Stream, StreamExt};
+use crate::virtual_dom::{ElRef, Ev};
+use futures::stream::{self, Stream, StreamExt};
 use gloo_timers::future::IntervalStream;
-use web_sys::Event;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    Event, IntersectionObserver, IntersectionObserverEntry, MediaQueryListEvent, ResizeObserver,
+    ResizeObserverEntry, VisibilityState,
+};
 
 mod event_stream;
 use event_stream::EventStream;
@@ -10,11 +15,23 @@ use event_stream::EventStream;
 mod backoff_stream;
 use backoff_stream::BackoffStream;
 
+mod debounce_stream;
+use debounce_stream::DebounceStream;
+
+mod throttle_stream;
+use throttle_stream::ThrottleStream;
+
+mod animation_frame_stream;
+use animation_frame_stream::AnimationFrameStream;
+
+mod observer_stream;
+use observer_stream::ObserverStream;
+
 // ------ Interval stream ------
 
 /// Stream no values on predefined time interval in milliseconds.
 ///
-/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
 ///
 /// # Example
 ///
@@ -22,11 +39,6 @@ use backoff_stream::BackoffStream;
 ///orders.stream(streams::interval(1000, || Msg::OnTick));
 ///orders.stream_with_handle(streams::interval(1000, || log!("Tick!")));
 /// ```
-///
-/// # Panics
-///
-/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-/// (It will be changed to a compile-time error).
 pub fn interval<MsU>(
     ms: u32,
     handler: impl FnOnce() -> MsU + Clone + 'static,
@@ -43,7 +55,7 @@ pub fn interval<MsU>(
 /// # Arguments
 ///
 /// * `max_seconds` - Typically `32` or `64` seconds. Default is `32`.
-/// * `handler` - Receives the number of retries (starting from 1); Has to return `Msg`, `Option<Msg>` or `()`.
+/// * `handler` - Receives the number of retries (starting from 1); Has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
 ///
 /// # Example
 ///
@@ -51,11 +63,6 @@ pub fn interval<MsU>(
 ///orders.stream(streams::backoff(None, |_retries| Msg::OnTick));
 ///orders.stream_with_handle(streams::backoff(Some(15), |_| log!("Tick!")));
 /// ```
-///
-/// # Panics
-///
-/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-/// (It will be changed to a compile-time error).
 pub fn backoff<MsU>(
     max_seconds: Option<u32>,
     handler: impl FnOnce(usize) -> MsU + Clone + 'static,
@@ -67,7 +74,7 @@ pub fn backoff<MsU>(
 
 /// Stream `Window` `web_sys::Event`s.
 ///
-/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
 ///
 /// # Example
 ///
@@ -75,11 +82,6 @@ pub fn backoff<MsU>(
 ///orders.stream(streams::window_event(Ev::Resize, |_| Msg::OnResize));
 ///orders.stream_with_handle(streams::window_event(Ev::Click, |_| log!("Clicked!")));
 /// ```
-///
-/// # Panics
-///
-/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-/// (It will be changed to a compile-time error).
 pub fn window_event<MsU>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(Event) -> MsU + Clone + 'static,
@@ -91,7 +93,7 @@ pub fn window_event<MsU>(
 
 /// Stream `Document` `web_sys::Event`s.
 ///
-/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
 ///
 /// # Example
 ///
@@ -99,14 +101,204 @@ pub fn window_event<MsU>(
 ///orders.stream(streams::document_event(Ev::SelectionChange, |_| Msg::OnSelection));
 ///orders.stream_with_handle(streams::document_event(Ev::SelectionChange, |_| log!("Selection changed!")));
 /// ```
-///
-/// # Panics
-///
-/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
-/// (It will be changed to a compile-time error).
 pub fn document_event<MsU>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(Event) -> MsU + Clone + 'static,
 ) -> impl Stream<Item = MsU> {
     EventStream::new(&document(), trigger.into()).map(move |event| handler.clone()(event))
 }
+
+// ------ Animation Frame stream ------
+
+/// Stream `RenderInfo` on every animation frame - use it for game loops and animations.
+/// `timestamp_delta` is the time elapsed since the previous frame (`None` for the first one).
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::animation_frame(Msg::OnTick));
+///model.game_loop = Some(orders.stream_with_handle(streams::animation_frame(Msg::OnTick)));
+/// ```
+pub fn animation_frame<MsU>(
+    handler: impl FnOnce(RenderInfo) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    AnimationFrameStream::new().map(move |render_info| handler.clone()(render_info))
+}
+
+// ------ Debounce ------
+
+/// Emit the latest item of `stream` once it has been quiet for `ms` milliseconds.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::debounce(
+///    300,
+///    streams::window_event(Ev::Resize, |_| Msg::OnResize),
+///));
+/// ```
+pub fn debounce<S: Stream>(ms: u32, stream: S) -> impl Stream<Item = S::Item> {
+    DebounceStream::new(ms, stream)
+}
+
+// ------ Throttle ------
+
+/// Emit at most one item of `stream` per `ms` milliseconds.
+/// The first item is emitted immediately and the latest skipped item at the end of each period.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::throttle(
+///    100,
+///    streams::window_event(Ev::MouseMove, |event| Msg::MouseMoved(event.unchecked_into())),
+///));
+/// ```
+pub fn throttle<S: Stream>(ms: u32, stream: S) -> impl Stream<Item = S::Item> {
+    ThrottleStream::new(ms, stream)
+}
+
+// ------ Media Query stream ------
+
+/// Stream whether the document matches the media `query`.
+/// The current state is streamed first, then each change.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::media_query("(prefers-color-scheme: dark)", Msg::DarkModeChanged));
+/// ```
+///
+/// # Panics
+///
+/// Panics when the `query` can't be parsed.
+pub fn media_query<MsU>(
+    query: &str,
+    handler: impl FnOnce(bool) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    let media_query_list = window()
+        .match_media(query)
+        .expect("match media query")
+        .expect("valid media query");
+
+    let changes = EventStream::new(&media_query_list, Ev::Change)
+        .map(|event: MediaQueryListEvent| event.matches());
+    stream::once(async move { media_query_list.matches() })
+        .chain(changes)
+        .map(move |matches| handler.clone()(matches))
+}
+
+// ------ Visibility stream ------
+
+/// Stream `VisibilityState` changes of the page
+/// (e.g. the user has switched to another browser tab).
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::visibility_change(|state| Msg::VisibilityChanged(state == VisibilityState::Visible)));
+/// ```
+pub fn visibility_change<MsU>(
+    handler: impl FnOnce(VisibilityState) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    EventStream::<Event>::new(&document(), Ev::VisibilityChange)
+        .map(move |_| handler.clone()(document().visibility_state()))
+}
+
+// ------ Online Status stream ------
+
+/// Stream `true` when the browser goes online and `false` when it goes offline.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::online_status(Msg::OnlineStatusChanged));
+/// ```
+pub fn online_status<MsU>(
+    handler: impl FnOnce(bool) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    let online = EventStream::<Event>::new(&window(), Ev::Online).map(|_| true);
+    let offline = EventStream::<Event>::new(&window(), Ev::Offline).map(|_| false);
+    stream::select(online, offline).map(move |online| handler.clone()(online))
+}
+
+// ------ Resize Observer stream ------
+
+/// Stream `ResizeObserverEntry`s of the element referenced by `el_ref`.
+///
+/// The element is observed once it's rendered, so you can start the stream in `init`.
+/// The observer is disconnected when the stream is dropped (e.g. with its `StreamHandle`).
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::resize_observer(&model.canvas, |entry| {
+///    Msg::CanvasResized(entry.content_rect().width())
+///}));
+/// ```
+pub fn resize_observer<E: Clone + JsCast, MsU>(
+    el_ref: &ElRef<E>,
+    handler: impl FnOnce(ResizeObserverEntry) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    ObserverStream::new(
+        el_ref.map_type(),
+        ResizeObserver::new,
+        ResizeObserver::observe,
+        ResizeObserver::disconnect,
+    )
+    .map(move |entry| handler.clone()(entry))
+}
+
+// ------ Intersection Observer stream ------
+
+/// Stream `IntersectionObserverEntry`s of the element referenced by `el_ref`
+/// (i.e. changes of the element's visibility in the viewport).
+///
+/// The element is observed once it's rendered, so you can start the stream in `init`.
+/// The observer is disconnected when the stream is dropped (e.g. with its `StreamHandle`).
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::intersection_observer(&model.sentinel, |entry| {
+///    IF!(entry.is_intersecting() => Msg::LoadNextPage)
+///}));
+/// ```
+pub fn intersection_observer<E: Clone + JsCast, MsU>(
+    el_ref: &ElRef<E>,
+    handler: impl FnOnce(IntersectionObserverEntry) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    ObserverStream::new(
+        el_ref.map_type(),
+        IntersectionObserver::new,
+        IntersectionObserver::observe,
+        IntersectionObserver::disconnect,
+    )
+    .map(move |entry| handler.clone()(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn media_query_streams_current_state_first() {
+        let mut matches = Box::pin(media_query("(min-width: 0px)", |matches| matches));
+        assert_eq!(matches.next().await, Some(true));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::app::RenderInfo;
+use crate::browser::util::window;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::stream::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+// ------ AnimationFrameStream ------
+
+/// Emits `RenderInfo` on every animation frame.
+/// The next frame is requested only when the previous one has been consumed.
+#[derive(Debug)]
+pub struct AnimationFrameStream {
+    callback: Closure<dyn FnMut(f64)>,
+    receiver: UnboundedReceiver<f64>,
+    request_id: Option<i32>,
+    previous_timestamp: Option<f64>,
+}
+
+impl AnimationFrameStream {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded();
+
+        // @TODO replace with `Closure::new` once stable.
+        let callback = Closure::wrap(Box::new(move |timestamp| {
+            // The receiver could have been dropped.
+            let _ = sender.unbounded_send(timestamp);
+        }) as Box<dyn FnMut(f64)>);
+
+        Self {
+            callback,
+            receiver,
+            request_id: None,
+            previous_timestamp: None,
+        }
+    }
+}
+
+impl Stream for AnimationFrameStream {
+    type Item = RenderInfo;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        match Stream::poll_next(Pin::new(&mut self.receiver), cx) {
+            Poll::Ready(Some(timestamp)) => {
+                self.request_id = None;
+                let timestamp_delta = self
+                    .previous_timestamp
+                    .map(|previous_timestamp| timestamp - previous_timestamp);
+                self.previous_timestamp = Some(timestamp);
+                Poll::Ready(Some(RenderInfo {
+                    timestamp,
+                    timestamp_delta,
+                }))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                if self.request_id.is_none() {
+                    let request_id = window()
+                        .request_animation_frame(self.callback.as_ref().unchecked_ref())
+                        .expect("request animation frame");
+                    self.request_id = Some(request_id);
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for AnimationFrameStream {
+    fn drop(&mut self) {
+        if let Some(request_id) = self.request_id {
+            window()
+                .cancel_animation_frame(request_id)
+                .expect("cancel animation frame");
+        }
+    }
+}
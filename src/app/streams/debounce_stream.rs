@@ -0,0 +1,80 @@
+use futures::future::FutureExt;
+use futures::stream::Stream;
+use gloo_timers::future::TimeoutFuture;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// ------ DebounceStream ------
+
+/// Emits the latest item once the inner stream has been quiet for `ms` milliseconds.
+/// The pending item is emitted immediately when the inner stream ends.
+pub struct DebounceStream<S: Stream> {
+    stream: Pin<Box<S>>,
+    ms: u32,
+    pending: Option<S::Item>,
+    timeout: Option<TimeoutFuture>,
+    stream_ended: bool,
+}
+
+impl<S: Stream> DebounceStream<S> {
+    pub fn new(ms: u32, stream: S) -> Self {
+        Self {
+            stream: Box::pin(stream),
+            ms,
+            pending: None,
+            timeout: None,
+            stream_ended: false,
+        }
+    }
+}
+
+// The inner stream is pinned in its `Box` and items are never pinned.
+impl<S: Stream> Unpin for DebounceStream<S> {}
+
+impl<S: Stream> Stream for DebounceStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        while !self.stream_ended {
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    self.pending = Some(item);
+                    // Restart the timer.
+                    self.timeout = Some(TimeoutFuture::new(self.ms));
+                }
+                Poll::Ready(None) => self.stream_ended = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if self.stream_ended {
+            self.timeout = None;
+            return Poll::Ready(self.pending.take());
+        }
+
+        if let Some(timeout) = self.timeout.as_mut() {
+            if timeout.poll_unpin(cx).is_ready() {
+                self.timeout = None;
+                return Poll::Ready(self.pending.take());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream::{self, StreamExt};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn emit_latest_item() {
+        let items = DebounceStream::new(10, stream::iter(1..=3))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items, vec![3]);
+    }
+}
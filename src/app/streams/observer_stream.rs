@@ -0,0 +1,91 @@
+use super::animation_frame_stream::AnimationFrameStream;
+use crate::virtual_dom::ElRef;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::stream::{Stream, StreamExt};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::Element;
+
+// ------ ObserverStream ------
+
+/// Streams entries of DOM observers (`ResizeObserver`, `IntersectionObserver`, ..)
+/// created for the element referenced by `ElRef`.
+///
+/// The element is observed once it's rendered - `ElRef` is checked on every animation frame
+/// until then. The observer is disconnected on drop.
+pub struct ObserverStream<O: 'static, Entry> {
+    observer: O,
+    disconnect: fn(&O),
+    observe: fn(&O, &Element),
+    el_ref: ElRef<Element>,
+    waiting_for_element: Option<AnimationFrameStream>,
+    _callback: Closure<dyn Fn(js_sys::Array)>,
+    receiver: UnboundedReceiver<JsValue>,
+    phantom: PhantomData<Entry>,
+}
+
+impl<O: 'static, Entry: JsCast> ObserverStream<O, Entry> {
+    pub fn new(
+        el_ref: ElRef<Element>,
+        create_observer: impl FnOnce(&js_sys::Function) -> Result<O, JsValue>,
+        observe: fn(&O, &Element),
+        disconnect: fn(&O),
+    ) -> Self {
+        let (sender, receiver) = unbounded();
+
+        // @TODO replace with `Closure::new` once stable.
+        let callback = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+            for entry in entries.iter() {
+                sender.unbounded_send(entry).unwrap();
+            }
+        }) as Box<dyn Fn(js_sys::Array)>);
+
+        let observer = create_observer(callback.as_ref().unchecked_ref()).expect("create observer");
+
+        Self {
+            observer,
+            disconnect,
+            observe,
+            el_ref,
+            waiting_for_element: Some(AnimationFrameStream::new()),
+            _callback: callback,
+            receiver,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<O, Entry> Unpin for ObserverStream<O, Entry> {}
+
+impl<O, Entry: JsCast> Stream for ObserverStream<O, Entry> {
+    type Item = Entry;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while let Some(animation_frames) = this.waiting_for_element.as_mut() {
+            if let Some(element) = this.el_ref.get() {
+                (this.observe)(&this.observer, &element);
+                this.waiting_for_element = None;
+                break;
+            }
+            match animation_frames.poll_next_unpin(cx) {
+                Poll::Ready(Some(_)) => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        this.receiver
+            .poll_next_unpin(cx)
+            .map(|entry| entry.map(JsCast::unchecked_into))
+    }
+}
+
+impl<O, Entry> Drop for ObserverStream<O, Entry> {
+    fn drop(&mut self) {
+        (self.disconnect)(&self.observer);
+    }
+}
@@ -0,0 +1,86 @@
+use futures::future::FutureExt;
+use futures::stream::Stream;
+use gloo_timers::future::TimeoutFuture;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// ------ ThrottleStream ------
+
+/// Emits at most one item per `ms` milliseconds.
+/// The first item is emitted immediately, the latest item received during the waiting period
+/// is emitted when the period ends (so the last item is never lost).
+pub struct ThrottleStream<S: Stream> {
+    stream: Pin<Box<S>>,
+    ms: u32,
+    trailing: Option<S::Item>,
+    period: Option<TimeoutFuture>,
+    stream_ended: bool,
+}
+
+impl<S: Stream> ThrottleStream<S> {
+    pub fn new(ms: u32, stream: S) -> Self {
+        Self {
+            stream: Box::pin(stream),
+            ms,
+            trailing: None,
+            period: None,
+            stream_ended: false,
+        }
+    }
+}
+
+// The inner stream is pinned in its `Box` and items are never pinned.
+impl<S: Stream> Unpin for ThrottleStream<S> {}
+
+impl<S: Stream> Stream for ThrottleStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if let Some(period) = self.period.as_mut() {
+            if period.poll_unpin(cx).is_ready() {
+                self.period = None;
+                if let Some(item) = self.trailing.take() {
+                    self.period = Some(TimeoutFuture::new(self.ms));
+                    return Poll::Ready(Some(item));
+                }
+            }
+        }
+
+        while !self.stream_ended {
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if self.period.is_none() {
+                        self.period = Some(TimeoutFuture::new(self.ms));
+                        return Poll::Ready(Some(item));
+                    }
+                    self.trailing = Some(item);
+                }
+                Poll::Ready(None) => self.stream_ended = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if self.stream_ended {
+            self.period = None;
+            return Poll::Ready(self.trailing.take());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream::{self, StreamExt};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn emit_first_and_trailing_item() {
+        let items = ThrottleStream::new(10, stream::iter(1..=3))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items, vec![1, 3]);
+    }
+}
@@ -1,8 +1,9 @@
 use indexmap::IndexMap;
 use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fmt;
-use std::{cell::RefCell, rc::Rc};
+use std::rc::Rc;
 use uuid::Uuid;
 
 // ------ SubManager ------
@@ -21,6 +22,7 @@ impl<Ms: 'static> SubManager<Ms> {
         }
     }
 
+    #[cfg(test)]
     pub fn subscribe<SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
@@ -28,53 +30,70 @@ impl<Ms: 'static> SubManager<Ms> {
         self.subscribe_with_priority(handler, i8::default());
     }
 
-    pub(crate) fn subscribe_with_priority<SubMs: 'static + Clone>(
+    pub fn subscribe_with_priority<SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
         priority: i8,
     ) {
-        let sub = Subscription::new_with_priority(handler, priority);
-        let (type_id, id) = (sub.type_id, sub.id);
-
-        let mut subs = self.subs.borrow_mut();
-        subs.entry(type_id)
-            .or_insert_with(IndexMap::new)
-            .insert(id, sub);
-
-        subs.entry(type_id).and_modify(|subs_group| {
-            subs_group.sort_by(|_, sub_a, _, sub_b| Ord::cmp(&sub_b.priority, &sub_a.priority))
-        });
+        self.insert(TypeId::of::<SubMs>(), erase_handler(handler), priority);
     }
 
-    pub fn subscribe_with_handle<SubMs: 'static + Clone>(
+    pub fn subscribe_with_handle_and_priority<SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
+        priority: i8,
     ) -> SubHandle {
-        let sub = Subscription::new(handler);
-        let (type_id, id) = (sub.type_id, sub.id);
-
-        let mut subs = self.subs.borrow_mut();
-        subs.entry(type_id)
-            .or_insert_with(IndexMap::new)
-            .insert(id, sub);
+        self.subscribe_erased_with_handle(TypeId::of::<SubMs>(), erase_handler(handler), priority)
+    }
 
-        subs.entry(type_id).and_modify(|subs_group| {
-            subs_group.sort_by(|_, sub_a, _, sub_b| Ord::cmp(&sub_b.priority, &sub_a.priority))
-        });
+    /// Subscribe the handler created by `erase_handler` for notifications of the type `type_id`.
+    pub(crate) fn subscribe_erased_with_handle(
+        &mut self,
+        type_id: TypeId,
+        handler: ErasedHandler<Ms>,
+        priority: i8,
+    ) -> SubHandle {
+        let id = self.insert(type_id, handler, priority);
 
         let subs = Rc::clone(&self.subs);
         SubHandle {
             unsubscriber: Box::new(move || {
-                subs.borrow_mut()
-                    .get_mut(&type_id)
-                    .expect("get subscriptions by `type_id`")
-                    .remove(&id)
-                    .expect("remove subscription");
+                // Subscriptions may have been already cleared by `App::unmount`.
+                if let Some(subs_group) = subs.borrow_mut().get_mut(&type_id) {
+                    subs_group.remove(&id);
+                }
             }),
         }
     }
 
-    pub fn notify(&self, notification: &Notification) -> Vec<Box<dyn FnOnce() -> Option<Ms>>> {
+    fn insert(&mut self, type_id: TypeId, handler: ErasedHandler<Ms>, priority: i8) -> Uuid {
+        let id = Uuid::new_v4();
+        let sub = Subscription {
+            type_id,
+            id,
+            handler,
+            priority,
+        };
+
+        let mut subs = self.subs.borrow_mut();
+        let subs_group = subs.entry(type_id).or_default();
+        subs_group.insert(id, sub);
+        // Higher priority first. The sort is stable - equal priorities keep the subscription order.
+        subs_group.sort_by(|_, sub_a, _, sub_b| Ord::cmp(&sub_b.priority, &sub_a.priority));
+        id
+    }
+
+    pub fn clear(&mut self) {
+        self.subs.borrow_mut().clear();
+    }
+
+    /// Create handlers of the subscribers in the priority order.
+    /// Handlers return `None` without invoking the subscriber once `propagation` is stopped.
+    pub fn notify(
+        &self,
+        notification: &Notification,
+        propagation: &Propagation,
+    ) -> Vec<Box<dyn FnOnce() -> Option<Ms>>> {
         self.subs
             .borrow()
             .get(&notification.type_id)
@@ -84,8 +103,15 @@ impl<Ms: 'static> SubManager<Ms> {
                     .map(|subscription| {
                         let handler = Rc::clone(&subscription.handler);
                         let message = Rc::clone(&notification.message);
+                        let propagation = propagation.clone();
                         let triggered_handler: Box<dyn FnOnce() -> Option<Ms>> =
-                            Box::new(move || handler(message));
+                            Box::new(move || {
+                                if propagation.is_stopped() {
+                                    None
+                                } else {
+                                    handler(message)
+                                }
+                            });
                         triggered_handler
                     })
                     .collect()
@@ -114,48 +140,46 @@ impl Drop for SubHandle {
     }
 }
 
+// ------ Propagation ------
+
+/// Shared by handlers of one notification delivery - see `Orders::stop_propagation`.
+#[derive(Debug, Clone, Default)]
+pub struct Propagation(Rc<Cell<bool>>);
+
+impl Propagation {
+    pub(crate) fn stop(&self) {
+        self.0.set(true);
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.0.get()
+    }
+}
+
 // ------ Subscription ------
 
+/// Subscription handler that accepts the notification message as `Any`.
+pub(crate) type ErasedHandler<Ms> = Rc<dyn Fn(Rc<dyn Any>) -> Option<Ms>>;
+
+/// Convert `FnOnce(SubMs) + Clone` to `ErasedHandler`.
+pub(crate) fn erase_handler<SubMs: 'static + Clone, Ms>(
+    handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
+) -> ErasedHandler<Ms> {
+    Rc::new(move |sub_msg: Rc<dyn Any>| {
+        let sub_msg = sub_msg
+            .downcast_ref::<SubMs>()
+            .expect("downcast to `SubMs`");
+        handler.clone()(sub_msg.clone())
+    })
+}
+
 struct Subscription<Ms> {
     type_id: TypeId,
     id: Uuid,
-    handler: Rc<dyn Fn(Rc<dyn Any>) -> Option<Ms>>,
+    handler: ErasedHandler<Ms>,
     priority: i8,
 }
 
-impl<Ms: 'static> Subscription<Ms> {
-    #[allow(clippy::shadow_unrelated)]
-    pub fn new<SubMs: 'static + Clone>(
-        handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-    ) -> Self {
-        Self::new_with_priority(handler, 0)
-    }
-
-    #[allow(clippy::shadow_unrelated)]
-    pub(crate) fn new_with_priority<SubMs: 'static + Clone>(
-        handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-        priority: i8,
-    ) -> Self {
-        // Convert `FnOnce + Clone` to `Fn`.
-        let handler = move |sub_msg: SubMs| handler.clone()(sub_msg);
-
-        // Convert `Fn(SubMs)` to `Fn(&Box<dyn Any>)` where `Any` is `SubMs`.
-        let handler = move |sub_msg: Rc<dyn Any>| {
-            let sub_msg = sub_msg
-                .downcast_ref::<SubMs>()
-                .expect("downcast to `SubMs`");
-            handler(sub_msg.clone())
-        };
-
-        Self {
-            type_id: TypeId::of::<SubMs>(),
-            id: Uuid::new_v4(),
-            handler: Rc::new(handler),
-            priority,
-        }
-    }
-}
-
 impl<Ms> fmt::Debug for Subscription<Ms> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Subscription")
@@ -181,4 +205,62 @@ impl Notification {
             message: Rc::new(message),
         }
     }
+
+    /// Returns the message if it's `SubMs`.
+    pub(crate) fn message<SubMs: 'static>(&self) -> Option<&SubMs> {
+        self.message.downcast_ref::<SubMs>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Orders;
+    use crate::testing::TestOrders;
+
+    #[derive(Clone)]
+    struct Ping;
+
+    fn run(handlers: Vec<Box<dyn FnOnce() -> Option<&'static str>>>) -> Vec<&'static str> {
+        handlers
+            .into_iter()
+            .filter_map(|handler| handler())
+            .collect()
+    }
+
+    #[test]
+    fn higher_priority_first_and_stopped_propagation() {
+        let mut sub_manager = SubManager::new();
+        sub_manager.subscribe(|_: Ping| Some("default"));
+        sub_manager.subscribe_with_priority(|_: Ping| Some("low"), -5);
+        let _handle = sub_manager.subscribe_with_handle_and_priority(|_: Ping| Some("high"), 10);
+        sub_manager.subscribe(|_: Ping| Some("default 2"));
+
+        let propagation = Propagation::default();
+        let handlers = sub_manager.notify(&Notification::new(Ping), &propagation);
+        assert_eq!(run(handlers), vec!["high", "default", "default 2", "low"]);
+
+        let propagation = Propagation::default();
+        let mut handlers = sub_manager
+            .notify(&Notification::new(Ping), &propagation)
+            .into_iter();
+        assert_eq!(handlers.next().unwrap()(), Some("high"));
+        propagation.stop();
+        assert!(run(handlers.collect()).is_empty());
+    }
+
+    #[test]
+    fn subscription_priorities_and_stopped_propagation() {
+        let mut orders = TestOrders::<&'static str>::new();
+        orders
+            .subscribe(|_: Ping| "default")
+            .subscribe_with_priority(1, |_: Ping| "high");
+        assert_eq!(orders.notify_subscribers(Ping), vec!["high", "default"]);
+
+        assert!(!orders.propagation_stopped());
+        orders.stop_propagation();
+        assert!(orders.propagation_stopped());
+        orders.clear();
+        assert!(!orders.propagation_stopped());
+    }
 }
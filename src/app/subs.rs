@@ -1,4 +1,5 @@
 use crate::browser::Url;
+use serde::de::DeserializeOwned;
 
 // ------ UrlRequested sub ------
 
@@ -18,3 +19,43 @@ pub use url_requested::UrlRequested;
 /// ```
 #[derive(Debug, Clone)]
 pub struct UrlChanged(pub Url);
+
+impl UrlChanged {
+    pub const fn url(&self) -> &Url {
+        &self.0
+    }
+
+    /// Returns the value attached to the history entry the app has navigated to
+    /// by `Url::go_and_push_with_state` or `Url::go_and_replace_with_state`.
+    ///
+    /// Returns `None` when there is no value or it cannot be deserialized to `T`.
+    pub fn state<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.0.history_state()?.clone()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum WizardStep {
+        Cart,
+        Payment,
+    }
+
+    #[test]
+    fn url_changed_state() {
+        let url = Url::new().set_path(["checkout"]);
+        let url_changed = UrlChanged(
+            url.clone()
+                .with_history_state(Some(serde_json::json!("Payment"))),
+        );
+        assert_eq!(url_changed.state(), Some(WizardStep::Payment));
+        assert_eq!(url_changed.state::<u32>(), None);
+        // The state doesn't affect the comparison.
+        assert_eq!(url_changed.url(), &url);
+        assert_eq!(UrlChanged(url).state::<WizardStep>(), None);
+    }
+}
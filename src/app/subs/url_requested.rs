@@ -86,8 +86,13 @@ impl UrlRequest {
 
     /// Flag the url request as handled and prevent page refresh.
     /// - It's almost the same like `handled()` method, but Seed calls `prevent_default` on the click event.
+    /// - The page isn't refreshed even when the notification propagation is stopped
+    ///   (see `Orders::stop_propagation`).
     pub fn handled_and_prevent_refresh(self) {
         self.status.set(UrlRequestStatus::Handled(true));
+        if let Some(event) = self.event.borrow().as_ref() {
+            event.prevent_default();
+        }
     }
 
     pub fn status(&self) -> UrlRequestStatus {
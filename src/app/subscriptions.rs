@@ -0,0 +1,275 @@
+//! Declarative subscriptions - an alternative to storing `SubHandle`s and `StreamHandle`s.
+//!
+//! Pass your `subscriptions` function to `App::set_subscriptions`. It's called after each
+//! `update` and the runtime compares the returned keys with the running subscriptions:
+//! new keys are started and missing keys are stopped. Subscriptions with unchanged keys
+//! keep running - include the parameters into the key when they change
+//! (e.g. `format!("tick-{}", model.interval_ms)`).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! fn subscriptions(model: &Model) -> Subs<Msg> {
+//!     let mut subs = Subs::new().subscribe("url", Msg::UrlChanged);
+//!     if model.running {
+//!         subs = subs.interval("tick", 1000, || Msg::Tick);
+//!     }
+//!     subs.merge(page::subscriptions(&model.page).map_msg(Msg::Page))
+//! }
+//!
+//! #[wasm_bindgen(start)]
+//! pub fn start() {
+//!     App::start("app", init, update, view).set_subscriptions(subscriptions);
+//! }
+//! ```
+
+use super::sub_manager::{erase_handler, ErasedHandler};
+use super::{streams, HandlerOutput, MessageMapper, StreamHandle, SubHandle};
+use crate::browser::web_socket::{self, WebSocketMessage};
+use crate::virtual_dom::Ev;
+use futures::stream::{self, LocalBoxStream, Stream, StreamExt};
+use indexmap::IndexMap;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// ------ Subs ------
+
+/// Keyed subscriptions returned by your `subscriptions` function - see the module documentation.
+pub struct Subs<Ms: 'static> {
+    subs: IndexMap<String, Sub<Ms>>,
+}
+
+pub(crate) enum Sub<Ms: 'static> {
+    /// The stream is created when the subscription starts.
+    Stream(Box<dyn FnOnce() -> LocalBoxStream<'static, Option<Ms>>>),
+    Notification(TypeId, ErasedHandler<Ms>),
+}
+
+impl<Ms: 'static> Default for Subs<Ms> {
+    fn default() -> Self {
+        Self {
+            subs: IndexMap::new(),
+        }
+    }
+}
+
+impl<Ms: 'static> Subs<Ms> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to notifications of the type `SubMs` - see `Orders::subscribe`.
+    ///
+    /// The handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    pub fn subscribe<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        mut self,
+        key: impl ToString,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> Self {
+        let handler = move |sub_msg: SubMs| handler.clone()(sub_msg).into_msg();
+        self.subs.insert(
+            key.to_string(),
+            Sub::Notification(TypeId::of::<SubMs>(), erase_handler(handler)),
+        );
+        self
+    }
+
+    /// Run the stream created by `create_stream` - see `Orders::stream`.
+    /// `create_stream` is called when the subscription starts.
+    ///
+    /// ```rust,no_run
+    ///Subs::new().stream("resize", || streams::window_event(Ev::Resize, |_| Msg::OnResize))
+    /// ```
+    ///
+    /// The output has to be `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    pub fn stream<MsU: HandlerOutput<Ms, Kind>, Kind, S: Stream<Item = MsU> + 'static>(
+        mut self,
+        key: impl ToString,
+        create_stream: impl FnOnce() -> S + 'static,
+    ) -> Self {
+        self.subs.insert(
+            key.to_string(),
+            Sub::Stream(Box::new(move || {
+                create_stream().map(|msg: MsU| msg.into_msg()).boxed_local()
+            })),
+        );
+        self
+    }
+
+    /// See `streams::interval`.
+    ///
+    /// The handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    pub fn interval<MsU: HandlerOutput<Ms, Kind> + 'static, Kind>(
+        self,
+        key: impl ToString,
+        ms: u32,
+        handler: impl FnOnce() -> MsU + Clone + 'static,
+    ) -> Self {
+        self.stream(key, move || streams::interval(ms, handler))
+    }
+
+    /// See `streams::window_event`.
+    ///
+    /// The handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    pub fn window_event<MsU: HandlerOutput<Ms, Kind> + 'static, Kind>(
+        self,
+        key: impl ToString,
+        trigger: impl Into<Ev>,
+        handler: impl FnOnce(web_sys::Event) -> MsU + Clone + 'static,
+    ) -> Self {
+        let trigger = trigger.into();
+        self.stream(key, move || streams::window_event(trigger, handler))
+    }
+
+    /// Open a Web Socket and handle its messages. The connection is closed
+    /// when the subscription stops. Use `WebSocket::builder` when you need to send messages
+    /// or handle other socket events.
+    ///
+    /// The handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
+    pub fn web_socket<MsU: HandlerOutput<Ms, Kind> + 'static, Kind>(
+        self,
+        key: impl ToString,
+        url: impl Into<String>,
+        handler: impl FnOnce(WebSocketMessage) -> MsU + Clone + 'static,
+    ) -> Self {
+        let url = url.into();
+        self.stream(key, move || match web_socket::message_stream(&url) {
+            Ok(messages) => messages
+                .map(move |message| handler.clone()(message))
+                .boxed_local(),
+            Err(error) => {
+                crate::error!("Web Socket subscription failed", url, error);
+                stream::empty().boxed_local()
+            }
+        })
+    }
+
+    /// Add all subscriptions from `other`. Subscriptions with the same key are replaced.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.subs.extend(other.subs);
+        self
+    }
+
+    /// Keys of the subscriptions - useful in tests.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.subs.keys().map(String::as_str)
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Subs<Ms> {
+    type SelfWithOtherMs = Subs<OtherMs>;
+
+    /// Map messages of child module's subscriptions.
+    /// The keys are shared - prefix them in child modules to prevent collisions.
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> Subs<OtherMs> {
+        let f = Rc::new(move |msg: Ms| f.clone()(msg));
+        let subs = self
+            .subs
+            .into_iter()
+            .map(|(key, sub)| {
+                let f = Rc::clone(&f);
+                let sub = match sub {
+                    Sub::Stream(create_stream) => Sub::Stream(Box::new(move || {
+                        create_stream()
+                            .map(move |msg| msg.map(|msg| f(msg)))
+                            .boxed_local()
+                    })),
+                    Sub::Notification(type_id, handler) => Sub::Notification(
+                        type_id,
+                        Rc::new(move |sub_msg| handler(sub_msg).map(|msg| f(msg))),
+                    ),
+                };
+                (key, sub)
+            })
+            .collect();
+        Subs { subs }
+    }
+}
+
+// ------ ActiveSub ------
+
+/// Handle of a started subscription. The subscription is stopped on drop.
+// Handles are never read, they are only dropped.
+#[allow(dead_code)]
+pub(crate) enum ActiveSub {
+    Stream(StreamHandle),
+    Notification(SubHandle),
+}
+
+/// Stop subscriptions missing in `subs` and start the new ones.
+pub(crate) fn diff<Ms, H>(
+    active: &mut HashMap<String, H>,
+    subs: Subs<Ms>,
+    mut start: impl FnMut(Sub<Ms>) -> H,
+) {
+    active.retain(|key, _| subs.subs.contains_key(key));
+    for (key, sub) in subs.subs {
+        active.entry(key).or_insert_with(|| start(sub));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[derive(Clone)]
+    struct Tick;
+
+    fn keys<Ms>(subs: &Subs<Ms>) -> Vec<&str> {
+        subs.keys().collect()
+    }
+
+    #[test]
+    fn map_and_merge() {
+        let child = Subs::new()
+            .stream("child-numbers", || stream::iter(vec![1, 2]))
+            .subscribe("child-tick", |_: Tick| 3);
+        let subs = Subs::new()
+            .subscribe("tick", |_: Tick| -1)
+            .merge(child.map_msg(|number: i32| number * 10));
+        assert_eq!(keys(&subs), vec!["tick", "child-numbers", "child-tick"]);
+
+        let mut outputs = Vec::new();
+        for (_, sub) in subs.subs {
+            match sub {
+                Sub::Stream(create_stream) => {
+                    outputs.extend(block_on(create_stream().collect::<Vec<_>>()));
+                }
+                Sub::Notification(type_id, handler) => {
+                    assert_eq!(type_id, TypeId::of::<Tick>());
+                    outputs.push(handler(Rc::new(Tick)));
+                }
+            }
+        }
+        assert_eq!(outputs, vec![Some(-1), Some(10), Some(20), Some(30)]);
+    }
+
+    #[test]
+    fn start_new_and_stop_missing_subs() {
+        let mut active = HashMap::new();
+        let mut started = 0;
+
+        let subs = Subs::<u8>::new()
+            .subscribe("a", |_: Tick| ())
+            .subscribe("b", |_: Tick| ());
+        diff(&mut active, subs, |_| {
+            started += 1;
+            started
+        });
+        assert_eq!(active.len(), 2);
+
+        let subs = Subs::<u8>::new()
+            .subscribe("b", |_: Tick| ())
+            .subscribe("c", |_: Tick| ());
+        diff(&mut active, subs, |_| {
+            started += 1;
+            started
+        });
+        assert_eq!(active.get("a"), None);
+        // "b" keeps running.
+        assert!(active.get("b").copied() < Some(3));
+        assert_eq!(active.get("c"), Some(&3));
+    }
+}
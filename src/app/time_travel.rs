@@ -0,0 +1,522 @@
+//! Time-travel debugger.
+//!
+//! It records every `Msg` (its `Debug` representation) together with the serialized `Model`
+//! after each `update` and renders a floating panel where you can step back and forth
+//! through the history. The `view` is rerendered from the stored model.
+//!
+//! The recorded session can be exported to JSON and imported back - e.g. QA can attach it
+//! to a bug report and a developer can replay it.
+//!
+//! _Note:_ When a new message arrives while you are in the past, the future steps are dropped
+//! and the history continues from the current step.
+//!
+//! The debugger runs until the app is unmounted, even if you drop its handle.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! #[wasm_bindgen(start)]
+//! pub fn start() {
+//!     let app = App::start("app", init, update, view);
+//!     if cfg!(debug_assertions) {
+//!         app.start_time_travel();
+//!     }
+//! }
+//! ```
+
+use super::App;
+use crate::browser::util;
+use crate::virtual_dom::IntoNodes;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt::Debug,
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+/// Convenient type alias.
+pub type Result<T> = std::result::Result<T, TimeTravelError>;
+
+// ------ TimeTravelError ------
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum TimeTravelError {
+    EmptySession,
+    SerdeError(serde_json::Error),
+}
+
+// ------ Session ------
+
+/// Recorded history - exported and imported as JSON.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Session {
+    pub steps: Vec<Step>,
+}
+
+/// The model after `update` with `msg`. `msg` is `None` for the initial model.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Step {
+    pub msg: Option<String>,
+    pub model: Value,
+}
+
+// ------ History ------
+
+#[derive(Debug, Default)]
+struct History {
+    session: Session,
+    current: usize,
+    pending_msg: Option<String>,
+}
+
+impl History {
+    fn record(&mut self, model: Value) {
+        if !self.session.steps.is_empty() {
+            // Forget the future when the app continues from the past.
+            self.session.steps.truncate(self.current + 1);
+        }
+        self.session.steps.push(Step {
+            msg: self.pending_msg.take(),
+            model,
+        });
+        self.current = self.session.steps.len() - 1;
+    }
+
+    fn go_to(&mut self, index: usize) -> Option<&Step> {
+        let step = self.session.steps.get(index)?;
+        self.current = index;
+        Some(step)
+    }
+
+    fn import(&mut self, session: Session) -> Result<&Step> {
+        if session.steps.is_empty() {
+            return Err(TimeTravelError::EmptySession);
+        }
+        self.current = session.steps.len() - 1;
+        self.session = session;
+        Ok(&self.session.steps[self.current])
+    }
+}
+
+// ------ TimeTravel ------
+
+/// Handle to the running time-travel debugger. Create it by `App::start_time_travel`.
+///
+/// The app keeps the debugger running - dropping the handle doesn't stop it.
+#[allow(clippy::module_name_repetitions)]
+pub struct TimeTravel<Ms: 'static, Mdl: 'static, INodes: IntoNodes<Ms>> {
+    inner: Rc<Inner<Ms, Mdl, INodes>>,
+}
+
+impl<Ms: 'static, Mdl: 'static, INodes: IntoNodes<Ms>> Clone for TimeTravel<Ms, Mdl, INodes> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Rc::clone(&self.inner),
+        }
+    }
+}
+
+struct Inner<Ms: 'static, Mdl: 'static, INodes: IntoNodes<Ms>> {
+    app: App<Ms, Mdl, INodes>,
+    history: RefCell<History>,
+    panel: RefCell<Option<Panel>>,
+}
+
+impl<Ms, Mdl, INodes> TimeTravel<Ms, Mdl, INodes>
+where
+    Ms: Debug + 'static,
+    Mdl: Serialize + DeserializeOwned + 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    pub(super) fn start(app: &App<Ms, Mdl, INodes>) -> Self {
+        let time_travel = Self {
+            inner: Rc::new(Inner {
+                app: app.clone(),
+                history: RefCell::new(History::default()),
+                panel: RefCell::new(None),
+            }),
+        };
+        time_travel.record(
+            app.data
+                .model
+                .borrow()
+                .as_ref()
+                .expect("model has been initialized"),
+        );
+
+        let weak_inner = Rc::downgrade(&time_travel.inner);
+        app.data
+            .msg_listeners
+            .borrow_mut()
+            .push(Box::new(move |msg| {
+                if let Some(inner) = weak_inner.upgrade() {
+                    inner.history.borrow_mut().pending_msg = Some(format!("{:?}", msg));
+                }
+            }));
+
+        let weak_inner = Rc::downgrade(&time_travel.inner);
+        app.data
+            .model_listeners
+            .borrow_mut()
+            .push(Box::new(move |model| {
+                if let Some(inner) = weak_inner.upgrade() {
+                    Self { inner }.record(model);
+                }
+            }));
+
+        time_travel
+            .inner
+            .panel
+            .replace(Some(Panel::new(Rc::downgrade(&time_travel.inner))));
+        time_travel.refresh_panel();
+
+        // The app owns the debugger - it replaces the previous one.
+        let inner: Rc<dyn Any> = Rc::clone(&time_travel.inner) as _;
+        app.data.time_travel.replace(Some(inner));
+        time_travel
+    }
+
+    /// Index of the displayed step.
+    pub fn current_step(&self) -> usize {
+        self.inner.history.borrow().current
+    }
+
+    /// The number of recorded steps (including the initial model).
+    pub fn step_count(&self) -> usize {
+        self.inner.history.borrow().session.steps.len()
+    }
+
+    /// Restore the model recorded in the step with the given `index` and rerender.
+    /// Returns `false` if there is no such step.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the stored model can't be deserialized.
+    pub fn go_to(&self, index: usize) -> bool {
+        let model = match self.inner.history.borrow_mut().go_to(index) {
+            Some(step) => step.model.clone(),
+            None => return false,
+        };
+        self.restore(model).expect("deserialize recorded model");
+        true
+    }
+
+    pub fn step_back(&self) -> bool {
+        self.current_step()
+            .checked_sub(1)
+            .is_some_and(|index| self.go_to(index))
+    }
+
+    pub fn step_forward(&self) -> bool {
+        self.go_to(self.current_step() + 1)
+    }
+
+    /// Serialize the recorded session to JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the session cannot be serialized.
+    pub fn export(&self) -> Result<String> {
+        serde_json::to_string(&self.inner.history.borrow().session)
+            .map_err(TimeTravelError::SerdeError)
+    }
+
+    /// Replace the recorded session with the imported one and restore its last step.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the session cannot be deserialized or it's empty.
+    pub fn import(&self, json: &str) -> Result<()> {
+        let session: Session = serde_json::from_str(json).map_err(TimeTravelError::SerdeError)?;
+        // Deserialize the model before the history is replaced to keep it valid on error.
+        let model = session
+            .steps
+            .last()
+            .map(|step| step.model.clone())
+            .ok_or(TimeTravelError::EmptySession)?;
+        let model: Mdl = serde_json::from_value(model).map_err(TimeTravelError::SerdeError)?;
+
+        self.inner.history.borrow_mut().import(session)?;
+        self.inner.app.data.model.replace(Some(model));
+        self.inner.app.rerender_vdom();
+        self.refresh_panel();
+        Ok(())
+    }
+
+    fn record(&self, model: &Mdl) {
+        let model = serde_json::to_value(model).expect("serialize model for time travel");
+        self.inner.history.borrow_mut().record(model);
+        self.refresh_panel();
+    }
+
+    fn restore(&self, model: Value) -> Result<()> {
+        let model: Mdl = serde_json::from_value(model).map_err(TimeTravelError::SerdeError)?;
+        self.inner.app.data.model.replace(Some(model));
+        self.inner.app.rerender_vdom();
+        self.refresh_panel();
+        Ok(())
+    }
+
+    fn refresh_panel(&self) {
+        if let Some(panel) = self.inner.panel.borrow().as_ref() {
+            let history = self.inner.history.borrow();
+            panel.refresh(&history);
+        }
+    }
+}
+
+// ------ Panel ------
+
+const PANEL_STYLE: &str = "position:fixed;right:8px;bottom:8px;z-index:2147483647;\
+    display:flex;flex-direction:column;gap:4px;width:320px;padding:8px;\
+    font:12px monospace;color:#eee;background:rgba(30,30,30,0.9);border-radius:4px";
+
+/// Floating panel - plain DOM outside of the app's mount point.
+struct Panel {
+    root: web_sys::Element,
+    slider: web_sys::HtmlInputElement,
+    label: web_sys::Element,
+    session_json: web_sys::HtmlTextAreaElement,
+    _listeners: Vec<Closure<dyn FnMut()>>,
+}
+
+impl Panel {
+    fn new<Ms, Mdl, INodes>(inner: Weak<Inner<Ms, Mdl, INodes>>) -> Self
+    where
+        Ms: Debug + 'static,
+        Mdl: Serialize + DeserializeOwned + 'static,
+        INodes: IntoNodes<Ms> + 'static,
+    {
+        let document = util::document();
+        let create = |tag: &str| {
+            document
+                .create_element(tag)
+                .expect("create time travel panel element")
+        };
+
+        let root = create("div");
+        root.set_attribute("style", PANEL_STYLE)
+            .expect("set time travel panel style");
+
+        let label = create("div");
+        let slider = create("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("cast to `HtmlInputElement`");
+        slider.set_type("range");
+        slider.set_min("0");
+
+        let controls = create("div");
+        let button = |text: &str| {
+            let button = create("button");
+            button.set_text_content(Some(text));
+            controls
+                .append_child(&button)
+                .expect("append time travel button");
+            button
+        };
+        let back = button("◀");
+        let forward = button("▶");
+        let export = button("Export");
+        let import = button("Import");
+
+        let session_json = create("textarea")
+            .dyn_into::<web_sys::HtmlTextAreaElement>()
+            .expect("cast to `HtmlTextAreaElement`");
+        session_json.set_placeholder("Exported / imported session JSON");
+        session_json.set_rows(3);
+
+        for child in &[&label, slider.as_ref(), &controls, session_json.as_ref()] {
+            root.append_child(child)
+                .expect("append time travel panel child");
+        }
+        util::body()
+            .append_child(&root)
+            .expect("append time travel panel to body");
+
+        let listeners = Self::listen(inner, &slider, [&back, &forward, &export, &import]);
+
+        Self {
+            root,
+            slider,
+            label,
+            session_json,
+            _listeners: listeners,
+        }
+    }
+
+    /// Attach panel control listeners - they have to be kept alive together with the panel.
+    fn listen<Ms, Mdl, INodes>(
+        inner: Weak<Inner<Ms, Mdl, INodes>>,
+        slider: &web_sys::HtmlInputElement,
+        [back, forward, export, import]: [&web_sys::Element; 4],
+    ) -> Vec<Closure<dyn FnMut()>>
+    where
+        Ms: Debug + 'static,
+        Mdl: Serialize + DeserializeOwned + 'static,
+        INodes: IntoNodes<Ms> + 'static,
+    {
+        // `Weak` prevents a reference cycle `Inner` -> `Panel` -> listener -> `Inner`.
+        let with_time_travel = move |f: fn(&TimeTravel<Ms, Mdl, INodes>)| {
+            let inner = inner.clone();
+            move || {
+                if let Some(inner) = inner.upgrade() {
+                    f(&TimeTravel { inner });
+                }
+            }
+        };
+
+        let mut listeners = Vec::new();
+        let mut listen = |target: &web_sys::EventTarget, event: &str, handler: Box<dyn FnMut()>| {
+            let closure = Closure::wrap(handler);
+            target
+                .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+                .expect("add time travel panel listener");
+            listeners.push(closure);
+        };
+
+        listen(
+            back,
+            "click",
+            Box::new(with_time_travel(|time_travel| {
+                time_travel.step_back();
+            })),
+        );
+        listen(
+            forward,
+            "click",
+            Box::new(with_time_travel(|time_travel| {
+                time_travel.step_forward();
+            })),
+        );
+        listen(
+            slider,
+            "input",
+            Box::new(with_time_travel(|time_travel| {
+                let index = time_travel
+                    .inner
+                    .panel
+                    .borrow()
+                    .as_ref()
+                    .and_then(|panel| panel.slider.value().parse().ok());
+                if let Some(index) = index {
+                    time_travel.go_to(index);
+                }
+            })),
+        );
+        listen(
+            export,
+            "click",
+            Box::new(with_time_travel(|time_travel| match time_travel.export() {
+                Ok(json) => {
+                    if let Some(panel) = time_travel.inner.panel.borrow().as_ref() {
+                        panel.session_json.set_value(&json);
+                    }
+                }
+                Err(error) => crate::error!("time travel export failed", error),
+            })),
+        );
+        listen(
+            import,
+            "click",
+            Box::new(with_time_travel(|time_travel| {
+                let json = time_travel
+                    .inner
+                    .panel
+                    .borrow()
+                    .as_ref()
+                    .map(|panel| panel.session_json.value());
+                if let Some(Err(error)) = json.map(|json| time_travel.import(&json)) {
+                    crate::error!("time travel import failed", error);
+                }
+            })),
+        );
+        listeners
+    }
+
+    fn refresh(&self, history: &History) {
+        let steps = &history.session.steps;
+        let last_index = steps.len().saturating_sub(1);
+        self.slider.set_max(&last_index.to_string());
+        self.slider.set_value(&history.current.to_string());
+
+        let msg = steps
+            .get(history.current)
+            .and_then(|step| step.msg.as_deref())
+            .unwrap_or("init");
+        self.label.set_text_content(Some(&format!(
+            "{} / {}: {}",
+            history.current, last_index, msg
+        )));
+    }
+}
+
+impl Drop for Panel {
+    fn drop(&mut self) {
+        self.root.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn history_with_steps(count: i32) -> History {
+        let mut history = History::default();
+        history.record(json!(0));
+        for counter in 1..count {
+            history.pending_msg = Some("Increment".to_owned());
+            history.record(json!(counter));
+        }
+        history
+    }
+
+    #[test]
+    fn record_and_go_to() {
+        let mut history = history_with_steps(3);
+        assert_eq!(history.current, 2);
+        assert_eq!(history.session.steps[0].msg, None);
+        assert_eq!(history.session.steps[2].msg.as_deref(), Some("Increment"));
+
+        assert_eq!(history.go_to(1).unwrap().model, json!(1));
+        assert_eq!(history.current, 1);
+        assert!(history.go_to(3).is_none());
+        assert_eq!(history.current, 1);
+    }
+
+    #[test]
+    fn record_in_the_past_drops_future() {
+        let mut history = history_with_steps(4);
+        history.go_to(1);
+        history.pending_msg = Some("Reset".to_owned());
+        history.record(json!(0));
+
+        assert_eq!(history.session.steps.len(), 3);
+        assert_eq!(history.current, 2);
+        assert_eq!(history.session.steps[2].msg.as_deref(), Some("Reset"));
+    }
+
+    #[test]
+    fn import_session() {
+        let exported = serde_json::to_string(&history_with_steps(2).session).unwrap();
+        assert_eq!(
+            exported,
+            r#"{"steps":[{"msg":null,"model":0},{"msg":"Increment","model":1}]}"#
+        );
+
+        let mut history = History::default();
+        let session: Session = serde_json::from_str(&exported).unwrap();
+        assert_eq!(history.import(session).unwrap().model, json!(1));
+        assert_eq!(history.current, 1);
+
+        assert!(matches!(
+            history.import(Session::default()),
+            Err(TimeTravelError::EmptySession)
+        ));
+        assert_eq!(history.session.steps.len(), 2);
+    }
+}
@@ -0,0 +1,364 @@
+//! Export your app as a Web Component (custom element).
+//!
+//! Each element instance runs its own `App` mounted into the element's shadow root.
+//! The app is started when the element is connected to the document and unmounted
+//! when the element is disconnected.
+//!
+//! - Observed attributes and properties are decoded into your messages.
+//! - Stylesheets are scoped to the shadow root - see `WebComponent::stylesheet`.
+//! - Use `Orders::dispatch_custom_event` to send events to the element's listeners.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! #[wasm_bindgen(start)]
+//! pub fn start() {
+//!     WebComponent::new("seed-counter", init, update, view)
+//!         .stylesheet(Stylesheet::inline("button { font-size: 2em; }"))
+//!         .observed_attributes(&["value"])
+//!         .attribute_decoder(|name, value| match name {
+//!             "value" => value.and_then(|value| value.parse().ok()).map(Msg::SetValue),
+//!             _ => None,
+//!         })
+//!         .properties(&["step"])
+//!         .property_decoder(|name, value| match name {
+//!             "step" => value.as_f64().map(|step| Msg::SetStep(step as i32)),
+//!             _ => None,
+//!         })
+//!         .define()
+//!         .expect("define `seed-counter`");
+//! }
+//! ```
+//!
+//! ```html
+//! <seed-counter value="5"></seed-counter>
+//! <script>
+//!     const counter = document.querySelector("seed-counter");
+//!     counter.step = 2;
+//!     counter.addEventListener("counter-changed", event => console.log(event.detail));
+//! </script>
+//! ```
+
+use super::{App, OrdersContainer};
+use crate::browser::{dom::Stylesheet, Url};
+use crate::virtual_dom::IntoNodes;
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsValue};
+
+/// The body of the function that creates and registers the custom element class.
+const DEFINE_CUSTOM_ELEMENT: &str = r#"
+    class SeedElement extends HTMLElement {
+        static get observedAttributes() {
+            return observedAttributes;
+        }
+        connectedCallback() {
+            // Properties set before the element has been upgraded shadow the accessors below -
+            // remove them and set them again through the accessors.
+            for (const property of properties) {
+                if (Object.prototype.hasOwnProperty.call(this, property)) {
+                    const value = this[property];
+                    delete this[property];
+                    this[property] = value;
+                }
+            }
+            connected(this);
+        }
+        disconnectedCallback() {
+            disconnected(this);
+        }
+        attributeChangedCallback(name, oldValue, newValue) {
+            if (oldValue !== newValue) {
+                attributeChanged(this, name, newValue);
+            }
+        }
+    }
+    for (const property of properties) {
+        const key = Symbol(property);
+        Object.defineProperty(SeedElement.prototype, property, {
+            get() {
+                return this[key];
+            },
+            set(value) {
+                this[key] = value;
+                propertySet(this, property, value);
+            },
+        });
+    }
+    customElements.define(name, SeedElement);
+"#;
+
+type InitFn<Ms, Mdl, INodes> = Rc<dyn Fn(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl>;
+type UpdateFn<Ms, Mdl, INodes> = Rc<dyn Fn(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>)>;
+type ViewFn<Mdl, INodes> = Rc<dyn Fn(&Mdl) -> INodes>;
+type AttributeDecoder<Ms> = Rc<dyn Fn(&str, Option<String>) -> Option<Ms>>;
+type PropertyDecoder<Ms> = Rc<dyn Fn(&str, JsValue) -> Option<Ms>>;
+type Instances<Ms, Mdl, INodes> = Rc<RefCell<Vec<(web_sys::Element, App<Ms, Mdl, INodes>)>>>;
+
+// ------ WebComponent ------
+
+/// Custom element definition - see the module documentation.
+pub struct WebComponent<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms>,
+{
+    name: String,
+    observed_attributes: Vec<String>,
+    properties: Vec<String>,
+    stylesheets: Vec<Stylesheet>,
+    init: InitFn<Ms, Mdl, INodes>,
+    update: UpdateFn<Ms, Mdl, INodes>,
+    view: ViewFn<Mdl, INodes>,
+    attribute_decoder: AttributeDecoder<Ms>,
+    property_decoder: PropertyDecoder<Ms>,
+}
+
+impl<Ms, Mdl, INodes> WebComponent<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    /// Create a custom element definition with the given `name` (it has to contain a hyphen).
+    ///
+    /// `init`, `update` and `view` are the same as in `App::start`, but `init` is called
+    /// for each element instance.
+    ///
+    /// _Note:_ Web Component apps don't handle `popstate` events and link clicks.
+    pub fn new(
+        name: impl Into<String>,
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + Clone + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            observed_attributes: Vec::new(),
+            properties: Vec::new(),
+            stylesheets: Vec::new(),
+            init: Rc::new(move |url, orders| init.clone()(url, orders)),
+            update: Rc::new(move |msg, model, orders| update.clone()(msg, model, orders)),
+            view: Rc::new(move |model| view.clone()(model)),
+            attribute_decoder: Rc::new(|_, _| None),
+            property_decoder: Rc::new(|_, _| None),
+        }
+    }
+
+    /// Attributes whose changes are passed to the `attribute_decoder`.
+    pub fn observed_attributes(mut self, names: &[&str]) -> Self {
+        self.observed_attributes = names.iter().map(ToString::to_string).collect();
+        self
+    }
+
+    /// Decode the attribute's name and its new value (`None` when removed) into a message.
+    ///
+    /// It's also called for all observed attributes when the element is connected.
+    pub fn attribute_decoder(
+        mut self,
+        decoder: impl Fn(&str, Option<String>) -> Option<Ms> + 'static,
+    ) -> Self {
+        self.attribute_decoder = Rc::new(decoder);
+        self
+    }
+
+    /// Element properties whose new values are passed to the `property_decoder`.
+    pub fn properties(mut self, names: &[&str]) -> Self {
+        self.properties = names.iter().map(ToString::to_string).collect();
+        self
+    }
+
+    /// Decode the property's name and its new value into a message.
+    ///
+    /// It's also called for all defined properties when the element is connected.
+    pub fn property_decoder(
+        mut self,
+        decoder: impl Fn(&str, JsValue) -> Option<Ms> + 'static,
+    ) -> Self {
+        self.property_decoder = Rc::new(decoder);
+        self
+    }
+
+    /// Add `stylesheet` to each element's shadow root.
+    ///
+    /// _Note:_ Create the `Stylesheet::Constructable` once - it's shared by all element instances.
+    pub fn stylesheet(mut self, stylesheet: Stylesheet) -> Self {
+        self.stylesheets.push(stylesheet);
+        self
+    }
+
+    /// Register the custom element by `customElements.define`.
+    ///
+    /// _Note:_ Custom elements can't be unregistered, so the element callbacks live forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the name is invalid or it has been already registered.
+    pub fn define(self) -> Result<(), JsValue> {
+        let instances: Instances<Ms, Mdl, INodes> = Rc::new(RefCell::new(Vec::new()));
+        let (init, update, view) = (self.init, self.update, self.view);
+        let (attribute_decoder, property_decoder) = (self.attribute_decoder, self.property_decoder);
+        let (observed_attributes, properties) = (self.observed_attributes, self.properties);
+        let stylesheets = self.stylesheets;
+
+        let connected = Closure::wrap(Box::new({
+            let instances = Rc::clone(&instances);
+            let observed_attributes = observed_attributes.clone();
+            let properties = properties.clone();
+            let (attribute_decoder, property_decoder) =
+                (Rc::clone(&attribute_decoder), Rc::clone(&property_decoder));
+            move |element: web_sys::Element| {
+                if find_app(&instances, &element).is_some() {
+                    return;
+                }
+                let app = start_app(&element, &stylesheets, &init, &update, &view);
+
+                for name in &observed_attributes {
+                    if let Some(value) = element.get_attribute(name) {
+                        app.update_with_option(attribute_decoder(name, Some(value)));
+                    }
+                }
+                for name in &properties {
+                    let value = js_sys::Reflect::get(&element, &JsValue::from_str(name))
+                        .unwrap_or(JsValue::UNDEFINED);
+                    if !value.is_undefined() {
+                        app.update_with_option(property_decoder(name, value));
+                    }
+                }
+                instances.borrow_mut().push((element, app));
+            }
+        }) as Box<dyn FnMut(web_sys::Element)>);
+
+        let disconnected = Closure::wrap(Box::new({
+            let instances = Rc::clone(&instances);
+            move |element: web_sys::Element| {
+                let app = {
+                    let mut instances = instances.borrow_mut();
+                    instances
+                        .iter()
+                        .position(|(el, _)| *el == element)
+                        .map(|index| instances.remove(index).1)
+                };
+                // Unmounting disconnects nested instances - `instances` mustn't be borrowed.
+                if let Some(app) = app {
+                    app.unmount();
+                    app.cfg.mount_point.remove();
+                }
+            }
+        }) as Box<dyn FnMut(web_sys::Element)>);
+
+        let attribute_changed = Closure::wrap(Box::new({
+            let instances = Rc::clone(&instances);
+            move |element: web_sys::Element, name: String, value: Option<String>| {
+                // Attributes of disconnected elements are decoded when they are connected.
+                if let Some(app) = find_app(&instances, &element) {
+                    app.update_with_option(attribute_decoder(&name, value));
+                }
+            }
+        })
+            as Box<dyn FnMut(web_sys::Element, String, Option<String>)>);
+
+        let property_set = Closure::wrap(Box::new({
+            let instances = Rc::clone(&instances);
+            move |element: web_sys::Element, name: String, value: JsValue| {
+                if let Some(app) = find_app(&instances, &element) {
+                    app.update_with_option(property_decoder(&name, value));
+                }
+            }
+        })
+            as Box<dyn FnMut(web_sys::Element, String, JsValue)>);
+
+        let define = js_sys::Function::new_with_args(
+            "name, observedAttributes, properties, connected, disconnected, attributeChanged, propertySet",
+            DEFINE_CUSTOM_ELEMENT,
+        );
+        let to_js_array = |names: Vec<String>| -> js_sys::Array {
+            names.into_iter().map(JsValue::from).collect()
+        };
+        let args: js_sys::Array = vec![
+            JsValue::from(self.name),
+            to_js_array(observed_attributes).into(),
+            to_js_array(properties).into(),
+            connected.as_ref().clone(),
+            disconnected.as_ref().clone(),
+            attribute_changed.as_ref().clone(),
+            property_set.as_ref().clone(),
+        ]
+        .into_iter()
+        .collect();
+        define.apply(&JsValue::NULL, &args)?;
+
+        connected.forget();
+        disconnected.forget();
+        attribute_changed.forget();
+        property_set.forget();
+        Ok(())
+    }
+}
+
+fn find_app<Ms, Mdl, INodes: IntoNodes<Ms>>(
+    instances: &Instances<Ms, Mdl, INodes>,
+    element: &web_sys::Element,
+) -> Option<App<Ms, Mdl, INodes>> {
+    instances
+        .borrow()
+        .iter()
+        .find(|(el, _)| el == element)
+        .map(|(_, app)| app.clone())
+}
+
+/// Start the app in the element's shadow root.
+/// The root is created and `stylesheets` are attached on the first connection.
+fn start_app<Ms, Mdl, INodes: IntoNodes<Ms> + 'static>(
+    element: &web_sys::Element,
+    stylesheets: &[Stylesheet],
+    init: &InitFn<Ms, Mdl, INodes>,
+    update: &UpdateFn<Ms, Mdl, INodes>,
+    view: &ViewFn<Mdl, INodes>,
+) -> App<Ms, Mdl, INodes> {
+    let shadow_root = element.shadow_root().unwrap_or_else(|| {
+        let shadow_root = element
+            .attach_shadow(&web_sys::ShadowRootInit::new(web_sys::ShadowRootMode::Open))
+            .expect("attach shadow root");
+        for stylesheet in stylesheets {
+            stylesheet
+                .attach(&shadow_root)
+                .expect("attach stylesheet to shadow root");
+        }
+        shadow_root
+    });
+
+    let (init, update, view) = (Rc::clone(init), Rc::clone(update), Rc::clone(view));
+    App::start_with_routing(
+        shadow_root,
+        move |url, orders| init(url, orders),
+        move |msg, model, orders| update(msg, model, orders),
+        move |model| view(model),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::Orders;
+    use crate::testing::TestOrders;
+    use std::collections::HashMap;
+
+    #[test]
+    fn record_custom_events() {
+        let mut orders = TestOrders::<Option<u8>>::new();
+        orders
+            .proxy(Some)
+            .dispatch_custom_event("clicked", ("a", 1));
+        // Events with details that can't be serialized aren't dispatched.
+        let invalid_detail = std::iter::once(((1, 2), 3)).collect::<HashMap<_, _>>();
+        orders.dispatch_custom_event("invalid", invalid_detail);
+
+        assert_eq!(
+            orders.custom_events(),
+            &[("clicked".to_owned(), serde_json::json!(["a", 1]))]
+        );
+        assert_eq!(orders.custom_event_errors().len(), 1);
+        assert_eq!(orders.custom_event_errors()[0].0, "invalid");
+    }
+}
@@ -1,9 +1,11 @@
 pub mod dom;
 pub mod fetch;
+pub mod routing_mode;
 pub mod service;
 pub mod url;
 pub mod util;
 pub mod web_socket;
 pub mod web_storage;
 
+pub use routing_mode::RoutingMode;
 pub use url::{Url, UrlSearch, DUMMY_BASE_URL};
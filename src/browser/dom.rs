@@ -5,9 +5,11 @@ pub mod cast;
 pub mod css_units;
 pub mod event_handler;
 pub mod namespace;
+pub mod stylesheet;
 pub mod virtual_dom_bridge;
 
 pub use namespace::Namespace;
+pub use stylesheet::Stylesheet;
 
 #[cfg(test)]
 pub mod tests {
@@ -2,22 +2,17 @@
 //! `web_sys::Event`
 
 use super::super::util;
+use crate::app::HandlerOutput;
 use crate::virtual_dom::{Ev, EventHandler};
-use std::rc::Rc;
 use wasm_bindgen::JsCast;
 
 /// Create an event that passes a String of field text, for fast input handling.
 #[allow(clippy::shadow_unrelated)]
-pub fn input_ev<Ms: 'static, MsU: 'static>(
+pub fn input_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(String) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(String) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
+    let handler = move |value: String| handler.clone()(value).into_msg();
     let handler = move |event: web_sys::Event| {
         let value = event
             .target()
@@ -34,16 +29,11 @@ pub fn input_ev<Ms: 'static, MsU: 'static>(
 /// Create an event that passes a `web_sys::KeyboardEvent`, allowing easy access
 /// to items like `key_code`() and key().
 #[allow(clippy::shadow_unrelated)]
-pub fn keyboard_ev<Ms: 'static, MsU: 'static>(
+pub fn keyboard_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::KeyboardEvent) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(web_sys::KeyboardEvent) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
+    let handler = move |value: web_sys::KeyboardEvent| handler.clone()(value).into_msg();
     let handler = move |event: web_sys::Event| {
         handler(event.dyn_ref::<web_sys::KeyboardEvent>().unwrap().clone())
     };
@@ -52,16 +42,11 @@ pub fn keyboard_ev<Ms: 'static, MsU: 'static>(
 
 /// See `keyboard_ev`
 #[allow(clippy::shadow_unrelated)]
-pub fn mouse_ev<Ms: 'static, MsU: 'static>(
+pub fn mouse_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::MouseEvent) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(web_sys::MouseEvent) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
+    let handler = move |value: web_sys::MouseEvent| handler.clone()(value).into_msg();
     let handler = move |event: web_sys::Event| {
         handler(event.dyn_ref::<web_sys::MouseEvent>().unwrap().clone())
     };
@@ -70,16 +55,11 @@ pub fn mouse_ev<Ms: 'static, MsU: 'static>(
 
 /// See `keyboard_ev`
 #[allow(clippy::shadow_unrelated)]
-pub fn touch_ev<Ms: 'static, MsU: 'static>(
+pub fn touch_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::TouchEvent) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(web_sys::TouchEvent) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
+    let handler = move |value: web_sys::TouchEvent| handler.clone()(value).into_msg();
     let handler = move |event: web_sys::Event| {
         handler(event.dyn_ref::<web_sys::TouchEvent>().unwrap().clone())
     };
@@ -88,16 +68,11 @@ pub fn touch_ev<Ms: 'static, MsU: 'static>(
 
 /// See `keyboard_ev`
 #[allow(clippy::shadow_unrelated)]
-pub fn drag_ev<Ms: 'static, MsU: 'static>(
+pub fn drag_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::DragEvent) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(web_sys::DragEvent) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
+    let handler = move |value: web_sys::DragEvent| handler.clone()(value).into_msg();
     let handler = move |event: web_sys::Event| {
         handler(event.dyn_ref::<web_sys::DragEvent>().unwrap().clone())
     };
@@ -106,16 +81,11 @@ pub fn drag_ev<Ms: 'static, MsU: 'static>(
 
 /// See `keyboard_ev`
 #[allow(clippy::shadow_unrelated)]
-pub fn pointer_ev<Ms: 'static, MsU: 'static>(
+pub fn pointer_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::PointerEvent) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(web_sys::PointerEvent) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
+    let handler = move |value: web_sys::PointerEvent| handler.clone()(value).into_msg();
     let handler = move |event: web_sys::Event| {
         handler(event.dyn_ref::<web_sys::PointerEvent>().unwrap().clone())
     };
@@ -125,7 +95,7 @@ pub fn pointer_ev<Ms: 'static, MsU: 'static>(
 /// Create an event that accepts a closure, and passes a `web_sys::Event`, allowing full control of
 /// event-handling.
 #[deprecated(since = "0.6.0", note = "Use `ev` instead.")]
-pub fn raw_ev<Ms: 'static, MsU: 'static>(
+pub fn raw_ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::Event) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
@@ -135,23 +105,14 @@ pub fn raw_ev<Ms: 'static, MsU: 'static>(
 /// Create an event handler that accepts a closure, and passes a `web_sys::Event`, allowing full control of
 /// event-handling.
 ///
-/// Handler has to return `Msg`, `Option<Msg>` or `()`.
-///
-/// # Panics
-///
-/// Panics when the handler doesn't return `Msg` or `()`. (It will be changed to a compile-time error).
+/// Handler has to return `Msg`, `Option<Msg>` or `()` - see `HandlerOutput`.
 #[allow(clippy::shadow_unrelated)]
-pub fn ev<Ms: 'static, MsU: 'static>(
+pub fn ev<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
     trigger: impl Into<Ev>,
     handler: impl FnOnce(web_sys::Event) -> MsU + 'static + Clone,
 ) -> EventHandler<Ms> {
-    let handler = map_callback_return_to_option_ms!(
-        dyn Fn(web_sys::Event) -> Option<Ms>,
-        handler.clone(),
-        "Handler can return only Msg, Option<Msg> or ()!",
-        Rc
-    );
-    EventHandler::new(trigger, move |event| handler(event))
+    let handler = move |value: web_sys::Event| handler.clone()(value).into_msg();
+    EventHandler::new(trigger, handler)
 }
 
 /// Create an event that passes no data, other than it occurred. Foregoes using a closure,
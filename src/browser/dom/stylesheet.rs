@@ -0,0 +1,96 @@
+use crate::browser::util::document;
+use wasm_bindgen::JsValue;
+use web_sys::ShadowRoot;
+
+// ------ Stylesheet ------
+
+/// CSS scoped to a `ShadowRoot` - the host page's styles don't affect the elements
+/// inside the root and vice versa.
+///
+/// Attach it by `App::attach_stylesheet` or `WebComponent::stylesheet`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let shadow_root = host.attach_shadow(&ShadowRootInit::new(ShadowRootMode::Open))?;
+/// let app = App::start(shadow_root, init, update, view);
+/// app.attach_stylesheet(&Stylesheet::inline("button { color: red; }"))?;
+/// ```
+#[derive(Debug, Clone)]
+pub enum Stylesheet {
+    /// Constructable stylesheet added to the root's `adoptedStyleSheets`.
+    /// One instance can be shared by multiple roots.
+    Constructable(web_sys::CssStyleSheet),
+    /// `<style>` element with the given CSS, inserted at the beginning of the root.
+    /// It works also in browsers without constructable stylesheets.
+    Inline(String),
+}
+
+impl Stylesheet {
+    /// Create `Stylesheet::Constructable` from `css`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the browser doesn't support constructable stylesheets.
+    pub fn constructable(css: &str) -> Result<Self, JsValue> {
+        let stylesheet = web_sys::CssStyleSheet::new()?;
+        stylesheet.replace_sync(css)?;
+        Ok(Self::Constructable(stylesheet))
+    }
+
+    /// Create `Stylesheet::Inline` from `css`.
+    pub fn inline(css: impl Into<String>) -> Self {
+        Self::Inline(css.into())
+    }
+
+    /// Attach the stylesheet to `shadow_root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the stylesheet cannot be adopted or the `<style>` element cannot be inserted.
+    pub fn attach(&self, shadow_root: &ShadowRoot) -> Result<(), JsValue> {
+        match self {
+            Self::Constructable(stylesheet) => {
+                // Copy the array - older browsers return a frozen one.
+                let stylesheets = js_sys::Array::from(&shadow_root.adopted_style_sheets());
+                stylesheets.push(stylesheet);
+                shadow_root.set_adopted_style_sheets(&stylesheets);
+            }
+            Self::Inline(css) => {
+                let style = document().create_element("style")?;
+                style.set_text_content(Some(css));
+                shadow_root.insert_before(&style, shadow_root.first_child().as_ref())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::GetElement;
+    use wasm_bindgen_test::*;
+    use web_sys::{ShadowRootInit, ShadowRootMode};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn inline_stylesheet_is_inserted_before_mount_point() {
+        let host = document().create_element("div").unwrap();
+        let shadow_root = host
+            .attach_shadow(&ShadowRootInit::new(ShadowRootMode::Open))
+            .unwrap();
+        let mount_point = shadow_root.clone().get_element().unwrap();
+
+        Stylesheet::inline("div { color: red; }")
+            .attach(&shadow_root)
+            .unwrap();
+
+        assert_eq!(
+            shadow_root.inner_html(),
+            r#"<style>div { color: red; }</style><div data-seed-root=""></div>"#
+        );
+        assert_eq!(shadow_root.get_element().unwrap(), mount_point);
+    }
+}
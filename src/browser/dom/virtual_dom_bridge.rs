@@ -1,7 +1,9 @@
 //! This file contains interactions with `web_sys`.
 
 use super::Namespace;
-use crate::virtual_dom::{At, AtValue, Attrs, El, Mailbox, Node, Style, Text};
+use crate::virtual_dom::{
+    lifecycle_hook, At, AtValue, Attrs, El, LifecycleEvent, Mailbox, Node, Portal, Style, Text,
+};
 use wasm_bindgen::JsCast;
 use web_sys::Document;
 
@@ -38,6 +40,12 @@ pub(crate) fn assign_ws_nodes<Ms>(document: &Document, node: &mut Node<Ms>) {
         Node::Element(el) => assign_ws_nodes_to_el(document, el),
         Node::Text(text) => assign_ws_nodes_to_text(document, text),
         Node::Empty => (),
+        Node::Lazy(lazy) => assign_ws_nodes(document, lazy.resolve()),
+        Node::Portal(portal) => {
+            for child in &mut portal.children {
+                assign_ws_nodes(document, child);
+            }
+        }
     }
 }
 
@@ -146,12 +154,29 @@ pub fn attach_children<Ms>(el: &mut El<Ms>, mailbox: &Mailbox<Ms>) {
         .expect("Missing websys el in attach_children");
     // appending the its children to the el_ws
     for child in &mut el.children {
-        match child {
-            // Raise the active level once per recursion.
-            Node::Element(child_el) => attach_el_and_children(child_el, el_ws, mailbox),
-            Node::Text(child_text) => attach_text_node(child_text, el_ws),
-            Node::Empty => (),
-        }
+        attach_node(child, el_ws, mailbox);
+    }
+}
+
+fn attach_node<Ms>(node: &mut Node<Ms>, parent: &web_sys::Node, mailbox: &Mailbox<Ms>) {
+    match node {
+        // Raise the active level once per recursion.
+        Node::Element(el) => attach_el_and_children(el, parent, mailbox),
+        Node::Text(text) => attach_text_node(text, parent),
+        Node::Empty => (),
+        Node::Lazy(lazy) => attach_node(lazy.resolve(), parent, mailbox),
+        Node::Portal(portal) => attach_portal(portal, mailbox),
+    }
+}
+
+/// Attaches portal's children to its target element.
+pub(crate) fn attach_portal<Ms>(portal: &mut Portal<Ms>, mailbox: &Mailbox<Ms>) {
+    let target = match portal.target.element() {
+        Some(target) => target.into(),
+        None => return,
+    };
+    for child in &mut portal.children {
+        attach_node(child, &target, mailbox);
     }
 }
 
@@ -178,17 +203,14 @@ pub fn attach_el_and_children<Ms>(el: &mut El<Ms>, parent: &web_sys::Node, mailb
 
     // appending the its children to the el_ws
     for child in &mut el.children {
-        match child {
-            // Raise the active level once per recursion.
-            Node::Element(child_el) => attach_el_and_children(child_el, el_ws, mailbox),
-            Node::Text(child_text) => attach_text_node(child_text, el_ws),
-            Node::Empty => (),
-        }
+        attach_node(child, el_ws, mailbox);
     }
 
     // Note: Call `set_default_element_state` after child appending,
     // otherwise it breaks autofocus in Firefox
     set_default_element_state(el_ws, el);
+
+    lifecycle_hook::invoke_hooks(el, LifecycleEvent::Insert, mailbox);
 }
 
 fn set_default_element_state<Ms>(el_ws: &web_sys::Node, el: &El<Ms>) {
@@ -214,6 +236,311 @@ fn set_default_element_state<Ms>(el_ws: &web_sys::Node, el: &El<Ms>) {
     }
 }
 
+// ------ Hydration ------
+
+/// Hydrate `children` - reuse `parent`'s existing DOM nodes where they match the vdom nodes,
+/// attach listeners to them and patch them only where they differ.
+/// Missing DOM nodes are created and redundant ones removed.
+///
+/// It allows to take over prerendered content without recreating it, so focus, scroll
+/// positions, media playback, etc. survive app startup.
+pub(crate) fn hydrate_children<Ms>(
+    document: &Document,
+    parent: &web_sys::Node,
+    children: &mut [Node<Ms>],
+    mailbox: &Mailbox<Ms>,
+) {
+    let mut next_node_ws = parent.first_child();
+
+    for child in children.iter_mut() {
+        child.resolve_lazy();
+    }
+
+    for index in 0..children.len() {
+        let next_is_text = matches!(
+            children.get(index + 1).map(Node::resolved),
+            Some(Node::Text(_))
+        );
+        let child = children[index].resolve_lazy();
+        if child.is_empty() {
+            continue;
+        }
+        // Portal's nodes live in another element - create them.
+        if let Node::Portal(portal) = child {
+            for child in &mut portal.children {
+                assign_ws_nodes(document, child);
+            }
+            attach_portal(portal, mailbox);
+            continue;
+        }
+        let node_ws = skip_unhydratable_nodes(parent, next_node_ws.take(), child.is_el());
+
+        match (child, node_ws) {
+            (Node::Element(el), Some(node_ws)) if el_matches_node_ws(el, &node_ws) => {
+                next_node_ws = node_ws.next_sibling();
+                hydrate_el(document, el, node_ws, mailbox);
+            }
+            (Node::Text(text), Some(node_ws))
+                if node_ws.node_type() == web_sys::Node::TEXT_NODE =>
+            {
+                hydrate_text(text, &node_ws, next_is_text);
+                next_node_ws = node_ws.next_sibling();
+                text.node_ws = Some(node_ws);
+            }
+            (child, old_node_ws) => {
+                #[cfg(debug_assertions)]
+                warn_about_hydration_mismatch(&format!(
+                    "expected {}, found {}",
+                    describe_node(child),
+                    old_node_ws
+                        .as_ref()
+                        .map_or_else(|| "nothing".to_owned(), describe_node_ws)
+                ));
+                next_node_ws = old_node_ws.as_ref().and_then(web_sys::Node::next_sibling);
+                create_node_for_hydration(document, child, parent, old_node_ws, mailbox);
+            }
+        }
+    }
+
+    // Remove DOM nodes without vdom counterparts.
+    while let Some(node_ws) = next_node_ws {
+        next_node_ws = node_ws.next_sibling();
+        #[cfg(debug_assertions)]
+        warn_about_hydration_mismatch(&format!(
+            "expected nothing, found {}",
+            describe_node_ws(&node_ws)
+        ));
+        remove_node(&node_ws, parent);
+    }
+}
+
+/// Remove comments and other nodes that can't be paired with vdom nodes.
+/// Whitespace-only text nodes are removed too when we're looking for an element.
+fn skip_unhydratable_nodes(
+    parent: &web_sys::Node,
+    mut node_ws: Option<web_sys::Node>,
+    looking_for_el: bool,
+) -> Option<web_sys::Node> {
+    while let Some(current) = node_ws {
+        let skip = match current.node_type() {
+            web_sys::Node::ELEMENT_NODE => false,
+            web_sys::Node::TEXT_NODE => {
+                looking_for_el && current.text_content().unwrap_or_default().trim().is_empty()
+            }
+            _ => true,
+        };
+        if !skip {
+            return Some(current);
+        }
+        node_ws = current.next_sibling();
+        remove_node(&current, parent);
+    }
+    None
+}
+
+fn el_matches_node_ws<Ms>(el: &El<Ms>, node_ws: &web_sys::Node) -> bool {
+    let element = match node_to_element(node_ws) {
+        Ok(element) => element,
+        Err(_) => return false,
+    };
+    let namespace = element
+        .namespace_uri()
+        .map_or(Namespace::Html, Namespace::from);
+    if el.namespace.as_ref().unwrap_or(&Namespace::Html) != &namespace {
+        return false;
+    }
+    let tag = el.tag.as_str();
+    match namespace {
+        Namespace::Html => tag.eq_ignore_ascii_case(&element.local_name()),
+        _ => tag == element.local_name(),
+    }
+}
+
+fn hydrate_el<Ms>(
+    document: &Document,
+    el: &mut El<Ms>,
+    node_ws: web_sys::Node,
+    mailbox: &Mailbox<Ms>,
+) {
+    hydrate_el_details(el, &node_ws);
+
+    for ref_ in &mut el.refs {
+        ref_.set(node_ws.clone());
+    }
+    el.event_handler_manager
+        .attach_listeners(node_ws.clone(), None, mailbox);
+
+    // Textarea's value is prerendered as its content, but we handle it through attribute `value`.
+    let is_textarea_without_children =
+        el.children.is_empty() && node_ws.dyn_ref::<web_sys::HtmlTextAreaElement>().is_some();
+    if !is_textarea_without_children {
+        hydrate_children(document, &node_ws, &mut el.children, mailbox);
+    }
+
+    el.node_ws = Some(node_ws);
+    lifecycle_hook::invoke_hooks(el, LifecycleEvent::Insert, mailbox);
+}
+
+/// Similar to `patch_el_details`, but the old state is read directly from the DOM.
+fn hydrate_el_details<Ms>(el: &El<Ms>, node_ws: &web_sys::Node) {
+    let element = match node_to_element(node_ws) {
+        Ok(element) => element,
+        Err(err) => {
+            crate::error(err);
+            return;
+        }
+    };
+    let has_style = !el.style.vals.is_empty();
+
+    // Remove attributes that aren't in the vdom.
+    element.get_attribute_names().for_each(&mut |name, _, _| {
+        let name = name.as_string().expect("problem converting attr to string");
+        let redundant = match name.as_str() {
+            "style" if has_style => false,
+            // `xmlns` is set for namespaced elements in `make_websys_el`.
+            "xmlns" if el.namespace.is_some() => false,
+            _ => !el.attrs.vals.contains_key(&At::from(name.clone())),
+        };
+        if redundant {
+            #[cfg(debug_assertions)]
+            warn_about_hydration_mismatch(&format!(
+                "redundant attribute `{}` on <{}>",
+                name, el.tag
+            ));
+            element
+                .remove_attribute(&name)
+                .expect("Removing an attribute");
+        }
+    });
+
+    for (at, at_value) in &el.attrs.vals {
+        if at == &At::Style && has_style {
+            continue;
+        }
+        let dom_value = element.get_attribute(at.as_str());
+        let up_to_date = match at_value {
+            AtValue::Ignored => dom_value.is_none(),
+            AtValue::None => dom_value.as_deref() == Some(""),
+            AtValue::Some(value) => dom_value.as_deref() == Some(value),
+        };
+        if !up_to_date {
+            #[cfg(debug_assertions)]
+            warn_about_hydration_mismatch(&format!(
+                "different value of attribute `{}` on <{}>",
+                at, el.tag
+            ));
+            set_attr_value(node_ws, at, at_value);
+        }
+
+        // We handle value in the vdom using attributes, but the DOM needs
+        // to use set_value or set_checked.
+        match at {
+            At::Value => match at_value {
+                AtValue::Some(value) => crate::util::set_value(node_ws, value),
+                AtValue::None | AtValue::Ignored => crate::util::set_value(node_ws, ""),
+            },
+            At::Checked => match at_value {
+                AtValue::Some(_) | AtValue::None => crate::util::set_checked(node_ws, true),
+                AtValue::Ignored => crate::util::set_checked(node_ws, false),
+            },
+            _ => Ok(()),
+        }
+        .unwrap_or_else(|err| {
+            crate::error(err);
+        })
+    }
+
+    if has_style && element.get_attribute("style") != Some(el.style.to_string()) {
+        #[cfg(debug_assertions)]
+        warn_about_hydration_mismatch(&format!("different style on <{}>", el.tag));
+        set_style(node_ws, &el.style);
+    }
+}
+
+fn hydrate_text(text: &Text, node_ws: &web_sys::Node, next_is_text: bool) {
+    let dom_text = node_ws.text_content().unwrap_or_default();
+    if dom_text == text.text {
+        return;
+    }
+    // Adjacent vdom text nodes are merged into one DOM text node during prerendering.
+    if next_is_text && dom_text.starts_with(text.text.as_ref()) {
+        if let Some(text_ws) = node_ws.dyn_ref::<web_sys::Text>() {
+            #[allow(clippy::cast_possible_truncation)]
+            let offset = text.text.encode_utf16().count() as u32;
+            if text_ws.split_text(offset).is_ok() {
+                return;
+            }
+        }
+    }
+    #[cfg(debug_assertions)]
+    warn_about_hydration_mismatch(&format!(
+        "expected text {:?}, found text {:?}",
+        text.text, dom_text
+    ));
+    node_ws.set_text_content(Some(&text.text));
+}
+
+/// Create a new DOM node for `node` and put it in the place of `old_node_ws`
+/// (or append it when there is no old node).
+fn create_node_for_hydration<Ms>(
+    document: &Document,
+    node: &mut Node<Ms>,
+    parent: &web_sys::Node,
+    old_node_ws: Option<web_sys::Node>,
+    mailbox: &Mailbox<Ms>,
+) {
+    assign_ws_nodes(document, node);
+    if let Node::Element(el) = node {
+        attach_children(el, mailbox);
+    }
+    let new_node_ws = node
+        .node_ws()
+        .expect("Missing websys node when hydrating")
+        .clone();
+
+    match old_node_ws {
+        Some(old_node_ws) => replace_child(&new_node_ws, &old_node_ws, parent),
+        None => insert_node(&new_node_ws, parent, None),
+    }
+
+    if let Node::Element(el) = node {
+        el.event_handler_manager
+            .attach_listeners(new_node_ws.clone(), None, mailbox);
+        // Note: Call `set_default_element_state` after child appending,
+        // otherwise it breaks autofocus in Firefox
+        set_default_element_state(&new_node_ws, el);
+        lifecycle_hook::invoke_hooks(el, LifecycleEvent::Insert, mailbox);
+    }
+}
+
+#[cfg(debug_assertions)]
+fn warn_about_hydration_mismatch(message: &str) {
+    web_sys::console::warn_1(&format!("Hydration mismatch: {}", message).into());
+}
+
+#[cfg(debug_assertions)]
+fn describe_node<Ms>(node: &Node<Ms>) -> String {
+    match node {
+        Node::Element(el) => format!("<{}>", el.tag),
+        Node::Text(text) => format!("text {:?}", text.text),
+        Node::Empty => "nothing".to_owned(),
+        Node::Portal(_) => "portal".to_owned(),
+        Node::Lazy(lazy) => lazy
+            .node()
+            .map_or_else(|| "lazy node".to_owned(), describe_node),
+    }
+}
+
+#[cfg(debug_assertions)]
+fn describe_node_ws(node_ws: &web_sys::Node) -> String {
+    match node_ws.node_type() {
+        web_sys::Node::TEXT_NODE => {
+            format!("text {:?}", node_ws.text_content().unwrap_or_default())
+        }
+        _ => format!("<{}>", node_ws.node_name().to_lowercase()),
+    }
+}
+
 /// Recursively remove all children.
 pub fn _remove_children(el: &web_sys::Node) {
     while let Some(child) = el.last_child() {
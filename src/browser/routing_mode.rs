@@ -0,0 +1,22 @@
+// ------ RoutingMode ------
+
+/// Where the app's `Url` is stored. Select it by `App::start_with_routing_mode`.
+///
+/// `Url::path`-based routing works the same way in all modes. Render links by
+/// `Url::to_href` to make them work also when they are opened in a new tab.
+///
+/// `Url::current`, `Url::go_and_push` and `Url::go_back` use the history of the app
+/// that calls them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RoutingMode {
+    /// `/admin/report?week=3` - the default.
+    #[default]
+    Path,
+    /// `/#/admin/report?week=3` - it doesn't require server support
+    /// (e.g. for static hosting).
+    Hash,
+    /// The url and history are kept in memory - the address bar and the browser history
+    /// aren't touched. Useful for tests and embedded widgets.
+    /// Only links inside the app are intercepted.
+    Memory,
+}
@@ -1,74 +1,692 @@
-use super::super::{
-    util::{self, ClosureNew},
-    Url,
+use super::super::{util, RoutingMode, Url};
+use crate::app::{scroll_restoration::Scroll, subs, Navigation, NavigationGuard, Notification};
+use enclose::enclose;
+use gloo_timers::callback::Timeout;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+    str::FromStr,
 };
-use crate::app::{subs, Notification};
-use std::rc::Rc;
+use uuid::Uuid;
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
 
-// Add a new route using history's `push_state` method.
-//
-// # References
-// * [MDN docs](https://developer.mozilla.org/en-US/docs/Web/API/History_API)
-pub fn push_route<U: Into<Url>>(url: U) -> Url {
-    let url = url.into();
+// ------ HistoryState ------
+
+/// State of the history entries pushed by Seed or by `Url::go_and_push`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub(crate) struct HistoryState {
     // We use data to evaluate the path instead of the path displayed in the url.
-    let data =
-        JsValue::from_str(&serde_json::to_string(&url).expect("Problem serializing route data"));
+    url: Url,
+    /// Identifies the entry - see `History::scroll_positions`.
+    /// It's `None` for entries pushed while no app with routing is running.
+    #[serde(default)]
+    key: Option<String>,
+    /// Position of the entry in the history - see `History::current_index`.
+    #[serde(default)]
+    index: Option<u32>,
+    /// Value attached by `Url::go_and_push_with_state` - see `subs::UrlChanged::state`.
+    #[serde(default)]
+    state: Option<serde_json::Value>,
+}
+
+impl HistoryState {
+    const fn new(url: Url, state: Option<serde_json::Value>) -> Self {
+        Self {
+            url,
+            key: None,
+            index: None,
+            state,
+        }
+    }
+
+    /// Parse the state of a history entry. Entries pushed by older Seed versions contain only `Url`.
+    fn parse(state: &str) -> Option<Self> {
+        serde_json::from_str(state)
+            .or_else(|_| serde_json::from_str(state).map(|url| Self::new(url, None)))
+            .ok()
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Problem serializing route data")
+    }
+}
+
+/// Add or replace the entry of the browser history. The url is written as a hash
+/// in `RoutingMode::Hash`.
+///
+/// # References
+/// * [MDN docs](https://developer.mozilla.org/en-US/docs/Web/API/History_API)
+fn write_browser_history(history_state: &HistoryState, routing_mode: RoutingMode, replace: bool) {
+    let data = JsValue::from_str(&history_state.to_json());
+    let url = history_state.url.to_href(routing_mode);
+    let history = util::history();
+    if replace {
+        history
+            .replace_state_with_url(&data, "", Some(&url))
+            .expect("Problem replacing state");
+    } else {
+        history
+            .push_state_with_url(&data, "", Some(&url))
+            .expect("Problem pushing state");
+    }
+}
+
+/// The url in the address bar. It's parsed from the hash in `RoutingMode::Hash`
+/// and it's empty in `RoutingMode::Memory` - the address bar isn't used.
+fn location_url(routing_mode: RoutingMode) -> Url {
+    match routing_mode {
+        RoutingMode::Path => {
+            let current_url = util::window().location().href().expect("get `href`");
+            Url::from_str(&current_url).expect("create `web_sys::Url` from the current URL")
+        }
+        RoutingMode::Hash => {
+            let hash = util::window().location().hash().expect("get `hash`");
+            match hash.trim_start_matches('#') {
+                "" => Url::new(),
+                hash => Url::from_str(hash).expect("create `web_sys::Url` from the current hash"),
+            }
+        }
+        RoutingMode::Memory => Url::new(),
+    }
+}
+
+/// The url of the current app - see `Url::current`.
+/// Apps with the browser history read it from the address bar.
+pub(crate) fn current_url() -> Url {
+    match current_history() {
+        Some(history) if history.routing_mode == RoutingMode::Memory => history.current_url(),
+        history => location_url(history.map_or(RoutingMode::Path, |history| history.routing_mode)),
+    }
+}
+
+// ------ EntryState ------
+
+/// State of the current entry of the browser history (or the memory history).
+#[allow(clippy::large_enum_variant)]
+enum EntryState {
+    Empty,
+    Seed(HistoryState),
+    /// The state hasn't been created by Seed.
+    Foreign,
+}
 
+impl EntryState {
+    fn from_browser_history() -> Self {
+        let state = util::history().state().unwrap_or(JsValue::NULL);
+        if state.is_null() || state.is_undefined() {
+            return Self::Empty;
+        }
+        state
+            .as_string()
+            .and_then(|state| HistoryState::parse(&state))
+            .map_or(Self::Foreign, Self::Seed)
+    }
+
+    fn from_memory_history(memory_history: &MemoryHistory) -> Self {
+        memory_history
+            .current()
+            .and_then(|state| HistoryState::parse(state))
+            .map_or(Self::Empty, Self::Seed)
+    }
+
+    /// `url` of the entry. The address bar is used for entries without Seed's state.
+    fn url(&self, routing_mode: RoutingMode) -> Url {
+        match self {
+            Self::Seed(history_state) => history_state
+                .url
+                .clone()
+                .with_history_state(history_state.state.clone()),
+            Self::Empty | Self::Foreign => location_url(routing_mode),
+        }
+    }
+
+    /// Position of the entry in the history. Entries without a state have been added
+    /// by the browser (e.g. the user has changed the hash in the address bar),
+    /// so they follow the entry the app has left.
+    /// Returns `None` when the position is unknown.
+    const fn index(&self, current_index: u32) -> Option<u32> {
+        match self {
+            Self::Seed(history_state) => history_state.index,
+            Self::Empty => Some(current_index.saturating_add(1)),
+            Self::Foreign => None,
+        }
+    }
+}
+
+/// Add a history entry to the history of the current app - see `Url::go_and_push`.
+pub(crate) fn push(url: Url, state: Option<serde_json::Value>) {
+    if let Some(history) = current_history() {
+        return history.push_with_state(url, state);
+    }
+    write_browser_history(&HistoryState::new(url, state), RoutingMode::Path, false);
+}
+
+/// Replace the current entry of the history of the current app - see `Url::go_and_replace`.
+pub(crate) fn replace(url: Url, state: Option<serde_json::Value>) {
+    if let Some(history) = current_history() {
+        return history.replace(url, state);
+    }
+    write_browser_history(&HistoryState::new(url, state), RoutingMode::Path, true);
+}
+
+/// Move through the history of the current app by `delta` entries (negative values move back)
+/// - see `Url::go_back`.
+pub(crate) fn go_in_current_history(delta: i32) {
+    match current_history() {
+        Some(history) => history.go(delta),
+        None => go(delta),
+    }
+}
+
+/// Move through the browser history by `delta` entries (negative values move back).
+pub(crate) fn go(delta: i32) {
     util::history()
-        .push_state_with_url(&data, "", Some(&url.to_string()))
-        .expect("Problem pushing state");
-    url
+        .go_with_delta(delta)
+        .expect("Problem moving through history");
+}
+
+// ------ MemoryHistory ------
+
+/// History used in `RoutingMode::Memory`. Entries are serialized `HistoryState`s.
+#[derive(Default)]
+struct MemoryHistory {
+    entries: Vec<String>,
+    index: usize,
 }
 
-pub fn setup_popstate_listener(
-    updated_listener: impl Fn(Closure<dyn FnMut(web_sys::Event)>) + 'static,
+impl MemoryHistory {
+    fn current(&self) -> Option<&String> {
+        self.entries.get(self.index)
+    }
+
+    fn write(&mut self, entry: String, replace: bool) {
+        if replace && !self.entries.is_empty() {
+            self.entries[self.index] = entry;
+        } else {
+            // Pushing removes the forward entries.
+            self.entries.truncate(self.index + 1);
+            self.entries.push(entry);
+            self.index = self.entries.len() - 1;
+        }
+    }
+
+    /// Returns `false` when `delta` is out of range - nothing happens, like in browsers.
+    fn go(&mut self, delta: i32) -> bool {
+        let index = i64::try_from(self.index).unwrap_or(i64::MAX) + i64::from(delta);
+        match usize::try_from(index) {
+            Ok(index) if delta != 0 && index < self.entries.len() => {
+                self.index = index;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn new_key() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Returns the number of entries between the positions (negative when `index` is behind).
+fn position_delta(index: u32, current_index: u32) -> i32 {
+    let delta = i64::from(index) - i64::from(current_index);
+    i32::try_from(delta).unwrap_or(if delta < 0 { i32::MIN } else { i32::MAX })
+}
+
+// ------ History ------
+
+thread_local! {
+    /// History of the app that controls the browser history - see `History::register`.
+    static PAGE_HISTORY: RefCell<Weak<History>> = const { RefCell::new(Weak::new()) };
+    /// History of the app whose code is running - see `History::scope`.
+    static CURRENT_HISTORY: RefCell<Option<Rc<History>>> = const { RefCell::new(None) };
+}
+
+fn page_history() -> Option<Rc<History>> {
+    PAGE_HISTORY.with(|page_history| page_history.borrow().upgrade())
+}
+
+/// The history of the app whose code is running. Outside of apps (e.g. in a `spawn_local`ed
+/// future) it's the history that controls the browser history.
+fn current_history() -> Option<Rc<History>> {
+    CURRENT_HISTORY
+        .with(|current_history| current_history.borrow().clone())
+        .or_else(page_history)
+}
+
+/// History entries visited by the app - the browser history or the memory history,
+/// depending on the app's `RoutingMode`.
+pub struct History {
+    routing_mode: RoutingMode,
+    /// The last url Seed navigated to.
+    current_url: RefCell<Url>,
+    current_key: RefCell<String>,
+    /// Position of the current entry - a rejected back or forward navigation
+    /// is undone by moving through the history by the difference of the positions.
+    current_index: Cell<u32>,
+    /// Scroll positions of the left entries.
+    scroll_positions: RefCell<HashMap<String, (f64, f64)>>,
+    /// Navigation started by `undo` or `redo` that hasn't been reported by the browser yet.
+    pending_go: Rc<Cell<Option<PendingGo>>>,
+    /// Clears `pending_go` - see `History::set_pending_go`.
+    pending_go_timeout: RefCell<Option<Timeout>>,
+    /// Entries of the app in `RoutingMode::Memory`.
+    memory: RefCell<MemoryHistory>,
+    /// Invoked on back or forward navigation - the memory alternative to `popstate`.
+    memory_listener: RefCell<Option<Rc<dyn Fn()>>>,
+}
+
+/// Navigation to the entry rejected by the navigation guard - see `History::undo`.
+enum Redo {
+    /// Move through the history by the number of entries.
+    Go(i32),
+    /// The position of the entry is unknown - write the url into the current entry.
+    Replace(Url),
+}
+
+/// How long `History::pending_go` waits for the browser to report the navigation.
+const PENDING_GO_TIMEOUT_MS: u32 = 500;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum PendingGo {
+    /// Return to the current entry after a rejected navigation.
+    Undo,
+    /// Navigate to the entry again after an asynchronous confirmation.
+    Redo,
+}
+
+impl History {
+    /// Attach a key and an index to the current history entry, unless it has a foreign state.
+    pub fn new(routing_mode: RoutingMode) -> Self {
+        let history = Self {
+            routing_mode,
+            current_url: RefCell::new(Url::new()),
+            current_key: RefCell::new(String::new()),
+            current_index: Cell::new(0),
+            scroll_positions: RefCell::new(HashMap::new()),
+            pending_go: Rc::new(Cell::new(None)),
+            pending_go_timeout: RefCell::new(None),
+            memory: RefCell::new(MemoryHistory::default()),
+            memory_listener: RefCell::new(None),
+        };
+        let entry_state = history.entry_state();
+        let url = entry_state.url(routing_mode);
+        let (key, index) = history.key_and_index(entry_state, url.clone(), 0);
+        history.current_url.replace(url);
+        history.current_key.replace(key);
+        history.current_index.set(index);
+        history
+    }
+
+    pub const fn routing_mode(&self) -> RoutingMode {
+        self.routing_mode
+    }
+
+    pub fn current_url(&self) -> Url {
+        self.current_url.borrow().clone()
+    }
+
+    /// Use this history in `Url::go_and_push`, `Url::go_and_replace` and `Url::go_back` called
+    /// outside of apps (see `History::scope`). The history registered last is used - there is
+    /// only one browser history in the page.
+    ///
+    /// Histories in `RoutingMode::Memory` aren't registered - they don't use the browser history.
+    pub(crate) fn register(self: &Rc<Self>) {
+        if self.routing_mode == RoutingMode::Memory {
+            return;
+        }
+        PAGE_HISTORY.with(|page_history| page_history.replace(Rc::downgrade(self)));
+    }
+
+    /// Unregister the history if it's still used by `Url::go_and_push` - see `register`.
+    pub(crate) fn unregister(self: &Rc<Self>) {
+        PAGE_HISTORY.with(|page_history| {
+            if page_history.borrow().ptr_eq(&Rc::downgrade(self)) {
+                page_history.replace(Weak::new());
+            }
+        });
+    }
+
+    /// Make `history` the history of the current app while `f` is running -
+    /// `Url::go_and_push`, `Url::go_and_replace`, `Url::go_back` and `Url::current` use it.
+    /// Apps without routing pass `None` - they use the browser history.
+    pub(crate) fn scope<R>(history: Option<&Rc<Self>>, f: impl FnOnce() -> R) -> R {
+        let previous =
+            CURRENT_HISTORY.with(|current_history| current_history.replace(history.map(Rc::clone)));
+        let output = f();
+        CURRENT_HISTORY.with(|current_history| current_history.replace(previous));
+        output
+    }
+
+    /// Save the scroll position of the current entry and add a new one.
+    pub fn push(&self, url: Url) {
+        self.push_with_state(url, None);
+    }
+
+    /// Like `push`, but attach `state` to the new entry.
+    pub(crate) fn push_with_state(&self, url: Url, state: Option<serde_json::Value>) {
+        self.save_scroll_position();
+        let key = new_key();
+        let index = self.current_index.get() + 1;
+        let history_state = HistoryState {
+            url: url.clone(),
+            key: Some(key.clone()),
+            index: Some(index),
+            state: state.clone(),
+        };
+        self.write(&history_state, false);
+        self.current_url.replace(url.with_history_state(state));
+        self.current_key.replace(key);
+        self.current_index.set(index);
+    }
+
+    /// Change the url and the state of the current entry. Its key and index are kept.
+    pub(crate) fn replace(&self, url: Url, state: Option<serde_json::Value>) {
+        let history_state = HistoryState {
+            url: url.clone(),
+            key: Some(self.current_key.borrow().clone()),
+            index: Some(self.current_index.get()),
+            state: state.clone(),
+        };
+        self.write(&history_state, true);
+        self.current_url.replace(url.with_history_state(state));
+    }
+
+    /// Move through the history by `delta` entries (negative values move back).
+    pub(crate) fn go(&self, delta: i32) {
+        if self.routing_mode != RoutingMode::Memory {
+            return go(delta);
+        }
+        let moved = self.memory.borrow_mut().go(delta);
+        // The listener is invoked outside of the borrow - it reads the new entry.
+        let listener = self.memory_listener.borrow().clone();
+        if let (true, Some(listener)) = (moved, listener) {
+            listener();
+        }
+    }
+
+    fn entry_state(&self) -> EntryState {
+        match self.routing_mode {
+            RoutingMode::Path | RoutingMode::Hash => EntryState::from_browser_history(),
+            RoutingMode::Memory => EntryState::from_memory_history(&self.memory.borrow()),
+        }
+    }
+
+    fn write(&self, history_state: &HistoryState, replace: bool) {
+        match self.routing_mode {
+            RoutingMode::Path | RoutingMode::Hash => {
+                write_browser_history(history_state, self.routing_mode, replace);
+            }
+            RoutingMode::Memory => self
+                .memory
+                .borrow_mut()
+                .write(history_state.to_json(), replace),
+        }
+    }
+
+    /// Returns the key and the index of the entry. The missing ones are attached
+    /// to Seed's state, foreign states are kept.
+    fn key_and_index(&self, entry_state: EntryState, url: Url, index: u32) -> (String, u32) {
+        let mut history_state = match entry_state {
+            EntryState::Seed(HistoryState {
+                key: Some(key),
+                index: Some(index),
+                ..
+            }) => return (key, index),
+            EntryState::Seed(history_state) => history_state,
+            EntryState::Empty => HistoryState::new(url, None),
+            EntryState::Foreign => return (new_key(), index),
+        };
+        let key = history_state.key.get_or_insert_with(new_key).clone();
+        let index = *history_state.index.get_or_insert(index);
+        self.write(&history_state, true);
+        (key, index)
+    }
+
+    /// Switch to the entry the browser has navigated to (back or forward).
+    /// Returns the scroll position saved when the entry has been left.
+    fn pop(&self, url: Url, entry_state: EntryState) -> Option<(f64, f64)> {
+        self.save_scroll_position();
+        // The position of entries with a foreign state is unknown - keep the current one.
+        let index = entry_state
+            .index(self.current_index.get())
+            .unwrap_or_else(|| self.current_index.get());
+        let (key, index) = self.key_and_index(entry_state, url.clone(), index);
+        let scroll_position = self.scroll_positions.borrow().get(&key).copied();
+        self.current_url.replace(url);
+        self.current_key.replace(key);
+        self.current_index.set(index);
+        scroll_position
+    }
+
+    /// Return to the current entry after the browser has navigated to another one.
+    /// Returns the navigation to the rejected entry - see `History::redo`.
+    ///
+    /// The current url is written into the entry when its position is unknown,
+    /// so no entries are added and the forward entries are kept.
+    fn undo(&self) -> Redo {
+        let entry_state = self.entry_state();
+        let current_index = self.current_index.get();
+        entry_state.index(current_index).map_or_else(
+            || {
+                let url = entry_state.url(self.routing_mode);
+                let current_url = self.current_url();
+                let state = current_url.history_state().cloned();
+                self.replace(current_url, state);
+                Redo::Replace(url)
+            },
+            |index| {
+                let delta = position_delta(index, current_index);
+                if delta != 0 {
+                    self.set_pending_go(PendingGo::Undo);
+                    self.go(-delta);
+                }
+                Redo::Go(delta)
+            },
+        )
+    }
+
+    /// Navigate to the entry rejected by `undo`.
+    /// Returns `true` when the browser doesn't report the navigation - the entry
+    /// has been replaced.
+    fn redo(&self, redo: Redo) -> bool {
+        match redo {
+            // The browser hasn't left the entry.
+            Redo::Go(0) => true,
+            Redo::Go(delta) => {
+                self.set_pending_go(PendingGo::Redo);
+                self.go(delta);
+                false
+            }
+            Redo::Replace(url) => {
+                let state = url.history_state().cloned();
+                self.write(&HistoryState::new(url, state), true);
+                true
+            }
+        }
+    }
+
+    /// Skip the guard for the next back or forward navigation.
+    ///
+    /// The browser doesn't always report the navigation (e.g. `hashchange` isn't fired when
+    /// the entries have the same hash) - `pending_go` is cleared after a while, so it doesn't
+    /// swallow the next navigation by the user.
+    fn set_pending_go(&self, pending_go: PendingGo) {
+        self.pending_go.set(Some(pending_go));
+        // The memory history reports the navigation synchronously.
+        if self.routing_mode != RoutingMode::Memory {
+            let pending_go = Rc::clone(&self.pending_go);
+            self.pending_go_timeout
+                .replace(Some(Timeout::new(PENDING_GO_TIMEOUT_MS, move || {
+                    pending_go.set(None);
+                })));
+        }
+    }
+
+    /// Take the navigation started by `undo` or `redo` - see `set_pending_go`.
+    fn take_pending_go(&self) -> Option<PendingGo> {
+        self.pending_go_timeout.replace(None);
+        self.pending_go.take()
+    }
+
+    fn save_scroll_position(&self) {
+        let window = util::window();
+        if let (Ok(x), Ok(y)) = (window.scroll_x(), window.scroll_y()) {
+            self.scroll_positions
+                .borrow_mut()
+                .insert(self.current_key.borrow().clone(), (x, y));
+        }
+    }
+}
+
+// ------ Listeners ------
+
+/// Event listener. It has to be removed by `remove`.
+pub struct Listener {
+    target: web_sys::EventTarget,
+    event: &'static str,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Listener {
+    fn new(
+        target: web_sys::EventTarget,
+        event: &'static str,
+        handler: impl FnMut(web_sys::Event) + 'static,
+    ) -> Self {
+        let closure = Closure::new(handler);
+        target
+            .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+            .expect("Problem adding listener");
+        Self {
+            target,
+            event,
+            closure,
+        }
+    }
+
+    pub fn remove(&self) {
+        self.target
+            .remove_event_listener_with_callback(self.event, self.closure.as_ref().unchecked_ref())
+            .expect("Problem removing listener");
+    }
+}
+
+/// Back and forward navigation listener - `popstate` in `RoutingMode::Path`,
+/// `hashchange` in `RoutingMode::Hash` and the memory history in `RoutingMode::Memory`.
+pub enum HistoryListener {
+    Event(Listener),
+    Memory(Rc<History>),
+}
+
+impl HistoryListener {
+    pub fn remove(&self) {
+        match self {
+            Self::Event(listener) => listener.remove(),
+            Self::Memory(history) => {
+                history.memory_listener.replace(None);
+            }
+        }
+    }
+}
+
+pub fn setup_history_listener(
     notify: impl Fn(Notification) + 'static,
+    guard: impl Fn(&Navigation) -> NavigationGuard + 'static,
+    scroll: impl Fn(Scroll) + 'static,
+    history: Rc<History>,
     base_path: Rc<Vec<String>>,
-) {
-    let closure = Closure::new(move |ev: web_sys::Event| {
-        let ev = ev
-            .dyn_ref::<web_sys::PopStateEvent>()
-            .expect("Problem casting as Popstate event");
-
-        let url = match ev.state().as_string() {
-            Some(state_str) => {
-                serde_json::from_str(&state_str).expect("Problem deserializing popstate state")
+) -> HistoryListener {
+    let navigate = Rc::new(enclose!((history, base_path) move || {
+        let entry_state = history.entry_state();
+        let url = entry_state.url(history.routing_mode);
+        let relative_url = url.clone().skip_base_path(&base_path);
+        let hash = url.hash().cloned();
+        let position = history.pop(url, entry_state);
+        scroll(Scroll::History { position, hash });
+        notify(Notification::new(subs::UrlChanged(relative_url)));
+    }));
+    let handler = enclose!((history) move || {
+        let guard = match history.take_pending_go() {
+            Some(PendingGo::Undo) => return,
+            // The navigation has been already confirmed.
+            Some(PendingGo::Redo) => NavigationGuard::Allow,
+            None => {
+                let url = history.entry_state().url(history.routing_mode);
+                guard(&Navigation::History(url.skip_base_path(&base_path)))
             }
-            // Only update when requested for an update by the user.
-            None => Url::current(),
         };
-
-        notify(Notification::new(subs::UrlChanged(
-            url.skip_base_path(&base_path),
-        )));
+        // The url has already been changed - restore it until the navigation is confirmed.
+        match guard.resolve() {
+            Ok(true) => navigate(),
+            Ok(false) => {
+                history.undo();
+            }
+            Err(confirmation) => {
+                let redo = history.undo();
+                let (history, navigate) = (Rc::clone(&history), Rc::clone(&navigate));
+                spawn_local(async move {
+                    if confirmation.await && history.redo(redo) {
+                        navigate();
+                    }
+                });
+            }
+        }
     });
 
-    (util::window().as_ref() as &web_sys::EventTarget)
-        .add_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
-        .expect("Problem adding popstate listener");
-
-    updated_listener(closure);
+    let window = util::window().into();
+    match history.routing_mode {
+        RoutingMode::Path => {
+            HistoryListener::Event(Listener::new(window, "popstate", move |_| handler()))
+        }
+        // `popstate` isn't fired when the user changes the hash in the address bar.
+        RoutingMode::Hash => {
+            HistoryListener::Event(Listener::new(window, "hashchange", move |_| handler()))
+        }
+        RoutingMode::Memory => {
+            history.memory_listener.replace(Some(Rc::new(handler)));
+            HistoryListener::Memory(history)
+        }
+    }
 }
 
 #[allow(clippy::needless_pass_by_value)]
 pub fn url_request_handler(
     sub_data: subs::UrlRequested,
     base_path: Rc<Vec<String>>,
+    guard: impl FnOnce(&Navigation) -> NavigationGuard,
+    scroll: impl FnOnce(Scroll) + 'static,
+    history: Rc<History>,
     notify: impl Fn(Notification) + 'static,
 ) {
     let subs::UrlRequested(url, request) = sub_data;
 
     match request.status() {
         subs::url_requested::UrlRequestStatus::Unhandled => {
-            push_route(url.clone());
             if let Some(event) = request.event.borrow_mut().take() {
                 event.prevent_default(); // Prevent page refresh
             }
-            notify(Notification::new(subs::UrlChanged(
-                url.skip_base_path(&base_path),
-            )));
+            let relative_url = url.clone().skip_base_path(&base_path);
+            let guard = guard(&Navigation::Request(relative_url.clone()));
+            let navigate = move || {
+                let hash = url.hash().cloned();
+                history.push(url);
+                scroll(Scroll::Push { hash });
+                notify(Notification::new(subs::UrlChanged(relative_url)));
+            };
+            match guard.resolve() {
+                Ok(true) => navigate(),
+                Ok(false) => (),
+                Err(confirmation) => spawn_local(async move {
+                    if confirmation.await {
+                        navigate();
+                    }
+                }),
+            }
         }
         subs::url_requested::UrlRequestStatus::Handled(prevent_default) => {
             if prevent_default {
@@ -80,14 +698,47 @@ pub fn url_request_handler(
     }
 }
 
+/// Set up a `beforeunload` listener that triggers the native prompt when `guard`
+/// doesn't allow `Navigation::Unload`.
+pub fn setup_before_unload_listener(
+    updated_listener: impl Fn(Closure<dyn FnMut(web_sys::Event)>),
+    guard: impl Fn(&Navigation) -> NavigationGuard + 'static,
+) {
+    let closure = Closure::new(move |event: web_sys::Event| {
+        // Dialogs are blocked during `beforeunload` - only the native prompt can be used.
+        if matches!(guard(&Navigation::Unload), NavigationGuard::Allow) {
+            return;
+        }
+        let event = event.unchecked_into::<web_sys::BeforeUnloadEvent>();
+        event.prevent_default();
+        // Because of Chrome
+        event.set_return_value("");
+    });
+
+    (util::window().as_ref() as &web_sys::EventTarget)
+        .add_event_listener_with_callback("beforeunload", closure.as_ref().unchecked_ref())
+        .expect("Problem adding beforeunload listener");
+
+    updated_listener(closure);
+}
+
 // Set up a listener that intercepts clicks on elements containing an Href attribute,
 // so we can prevent page refresh for internal links, and route internally.  Run this on load.
+// Links in shadow roots are intercepted too.
+// `target` is the document or the app's mount point in `RoutingMode::Memory`.
 #[allow(clippy::option_map_unit_fn)]
-pub fn setup_link_listener(notify: impl Fn(Notification) + 'static) {
-    let closure = Closure::new(move |event: web_sys::Event| {
-        event.target()
-            .and_then(|et| et.dyn_into::<web_sys::Element>().ok())
-            .and_then(|el| el.closest("a[href]").ok().flatten())
+pub fn setup_link_listener(
+    target: web_sys::EventTarget,
+    routing_mode: RoutingMode,
+    notify: impl Fn(Notification) + 'static,
+) -> Listener {
+    Listener::new(target, "click", move |event: web_sys::Event| {
+        // `composedPath` contains also elements inside (open) shadow roots,
+        // while `event.target` is retargeted to the shadow host.
+        event.composed_path()
+            .iter()
+            .filter_map(|target| target.dyn_into::<web_sys::Element>().ok())
+            .find(|el| el.matches("a[href]").unwrap_or_default())
             .and_then(|href_el| href_el.get_attribute("href"))
             // The first character being / or empty href indicates a rel link, which is what
             // we're intercepting.
@@ -96,6 +747,9 @@ pub fn setup_link_listener(notify: impl Fn(Notification) + 'static) {
             .and_then(|href| {
                 if href.is_empty() || href.starts_with('/') {
                     Some(href)
+                } else if routing_mode == RoutingMode::Hash && href.starts_with("#/") {
+                    // See `Url::to_href`.
+                    Some(href[1..].to_owned())
                 } else {
                     None
                 }
@@ -117,11 +771,94 @@ pub fn setup_link_listener(notify: impl Fn(Notification) + 'static) {
                     )));
                 }
             });
-    });
+    })
+}
+
+pub fn remove_before_unload_listener(closure: &Closure<dyn FnMut(web_sys::Event)>) {
+    (util::window().as_ref() as &web_sys::EventTarget)
+        .remove_event_listener_with_callback("beforeunload", closure.as_ref().unchecked_ref())
+        .expect("Problem removing beforeunload listener");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_history_state() {
+        let url = Url::new().set_path(["wizard"]);
+        let history_state = HistoryState::new(url.clone(), Some(serde_json::json!({"step": 2})));
+        let serialized = serde_json::to_string(&history_state).unwrap();
+        assert_eq!(HistoryState::parse(&serialized), Some(history_state));
 
-    (util::document().as_ref() as &web_sys::EventTarget)
-        .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
-        .expect("Problem setting up link interceptor");
+        // The state of entries pushed by older Seed versions.
+        let legacy = serde_json::to_string(&url).unwrap();
+        assert_eq!(
+            HistoryState::parse(&legacy),
+            Some(HistoryState::new(url, None))
+        );
 
-    closure.forget(); // todo: Can we store the closure somewhere to avoid using forget?
+        assert_eq!(HistoryState::parse("foreign state"), None);
+    }
+
+    #[test]
+    fn entry_positions() {
+        let mut history_state = HistoryState::new(Url::new(), None);
+        history_state.index = Some(2);
+        assert_eq!(EntryState::Seed(history_state).index(5), Some(2));
+        // Added by the browser after the current entry.
+        assert_eq!(EntryState::Empty.index(5), Some(6));
+        assert_eq!(EntryState::Foreign.index(5), None);
+        assert_eq!(
+            EntryState::Seed(HistoryState::new(Url::new(), None)).index(5),
+            None
+        );
+
+        assert_eq!(position_delta(2, 5), -3);
+        assert_eq!(position_delta(6, 5), 1);
+        assert_eq!(position_delta(u32::MAX, 0), i32::MAX);
+    }
+
+    #[test]
+    fn memory_history() {
+        let mut history = MemoryHistory::default();
+        assert_eq!(history.current(), None);
+        assert!(!history.go(-1));
+
+        history.write("a".to_owned(), true);
+        history.write("b".to_owned(), false);
+        history.write("c".to_owned(), false);
+        assert_eq!(history.current().map(String::as_str), Some("c"));
+
+        assert!(history.go(-2));
+        assert_eq!(history.current().map(String::as_str), Some("a"));
+        assert!(!history.go(-1));
+        assert!(!history.go(3));
+        assert!(!history.go(0));
+
+        history.write("d".to_owned(), true);
+        assert!(history.go(1));
+        assert_eq!(history.current().map(String::as_str), Some("b"));
+
+        // Pushing removes the forward entries.
+        history.write("e".to_owned(), false);
+        assert!(!history.go(1));
+        assert_eq!(history.entries, ["d", "b", "e"]);
+    }
+
+    #[test]
+    fn url_uses_history_of_current_app() {
+        let history = Rc::new(History::new(RoutingMode::Memory));
+        let url = Url::new().set_path(["settings"]);
+        History::scope(Some(&history), || {
+            url.go_and_replace_with_state(&"tab");
+            assert_eq!(Url::current(), url);
+            assert_eq!(
+                Url::current().history_state(),
+                Some(&serde_json::json!("tab"))
+            );
+        });
+        assert_eq!(history.current_url(), url);
+        assert!(CURRENT_HISTORY.with(|current_history| current_history.borrow().is_none()));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::browser::util;
+use crate::browser::{service::routing, util, RoutingMode};
 use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, collections::BTreeMap, fmt, str::FromStr};
 use wasm_bindgen::JsValue;
@@ -15,7 +15,7 @@ pub const DUMMY_BASE_URL: &str = "http://example.com";
 /// are considered different also during comparison.
 ///
 /// (If the features above are problems for you, create an [issue](https://github.com/seed-rs/seed/issues/new))
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Url {
     next_path_part_index: usize,
     next_hash_path_part_index: usize,
@@ -24,6 +24,22 @@ pub struct Url {
     hash: Option<String>,
     search: UrlSearch,
     invalid_components: Vec<String>,
+    /// The value attached to the history entry - see `subs::UrlChanged::state`.
+    /// It's stored in the history entry separately and ignored during comparison.
+    #[serde(skip)]
+    history_state: Option<serde_json::Value>,
+}
+
+impl PartialEq for Url {
+    fn eq(&self, other: &Self) -> bool {
+        self.next_path_part_index == other.next_path_part_index
+            && self.next_hash_path_part_index == other.next_hash_path_part_index
+            && self.path == other.path
+            && self.hash_path == other.hash_path
+            && self.hash == other.hash
+            && self.search == other.search
+            && self.invalid_components == other.invalid_components
+    }
 }
 
 impl Url {
@@ -34,42 +50,65 @@ impl Url {
 
     /// Change the browser URL, but do not trigger a page load.
     ///
-    /// This will add a new entry to the browser history.
+    /// This will add a new entry to the browser history. The running app saves the scroll
+    /// position of the current entry - see `ScrollRestoration`.
+    ///
+    /// The entry is added to the history of the app that calls it (in its `init`, `update`
+    /// or event handlers) - i.e. to the memory history in `RoutingMode::Memory`.
     ///
     /// # References
     /// * [MDN docs](https://developer.mozilla.org/en-US/docs/Web/API/History_API)
     pub fn go_and_push(&self) {
-        // We use data to evaluate the path instead of the path displayed in the url.
-        let data = JsValue::from_str(
-            &serde_json::to_string(&self).expect("Problem serializing route data"),
-        );
-
-        util::history()
-            .push_state_with_url(&data, "", Some(&self.to_string()))
-            .expect("Problem pushing state");
+        routing::push(self.clone(), None);
     }
 
     /// Change the browser URL, but do not trigger a page load.
     ///
     /// This will NOT add a new entry to the browser history.
+    /// The history of the app that calls it is used - see `Url::go_and_push`.
     ///
     /// # References
     /// * [MDN docs](https://developer.mozilla.org/en-US/docs/Web/API/History_API)
     pub fn go_and_replace(&self) {
-        // We use data to evaluate the path instead of the path displayed in the url.
-        let data = JsValue::from_str(
-            &serde_json::to_string(&self).expect("Problem serializing route data"),
-        );
+        routing::replace(self.clone(), None);
+    }
 
-        util::history()
-            .replace_state_with_url(&data, "", Some(&self.to_string()))
-            .expect("Problem pushing state");
+    /// Like `go_and_push`, but attach `state` to the new history entry.
+    /// Read it by `subs::UrlChanged::state` when the user navigates back to the entry.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Url::current().go_and_push_with_state(&WizardStep::Payment);
+    ///...
+    ///update(... Msg::UrlChanged(url_changed) => {
+    ///    model.step = url_changed.state().unwrap_or(WizardStep::Cart);
+    ///}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `state` cannot be serialized to JSON.
+    pub fn go_and_push_with_state(&self, state: &impl Serialize) {
+        routing::push(self.clone(), Some(to_state_value(state)));
+    }
+
+    /// Like `go_and_replace`, but attach `state` to the current history entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `state` cannot be serialized to JSON.
+    pub fn go_and_replace_with_state(&self, state: &impl Serialize) {
+        routing::replace(self.clone(), Some(to_state_value(state)));
     }
 
     /// Creates a new `Url` from the one that is currently set in the browser.
+    ///
+    /// It respects the `RoutingMode` of the app that calls it - e.g. the url is parsed
+    /// from the hash in `RoutingMode::Hash` and read from the memory history
+    /// in `RoutingMode::Memory`.
     pub fn current() -> Url {
-        let current_url = util::window().location().href().expect("get `href`");
-        Url::from_str(&current_url).expect("create `web_sys::Url` from the current URL")
+        routing::current_url()
     }
 
     /// Advances the internal path iterator and returns the next path part as `Option<&str>`.
@@ -357,8 +396,10 @@ impl Url {
     /// - `steps: 0` only reloads the current page.
     /// - Negative steps move you forward - use rather `Url::go_forward` instead.
     /// - If there is no previous page, this call does nothing.
+    ///
+    /// The history of the app that calls it is used - see `Url::go_and_push`.
     pub fn go_back(steps: i32) {
-        util::history().go_with_delta(-steps).expect("go back");
+        routing::go_in_current_history(-steps);
     }
 
     /// Move back in `History`.
@@ -367,7 +408,7 @@ impl Url {
     /// - Negative steps move you back - use rather `Url::go_back` instead.
     /// - If there is no next page, this call does nothing.
     pub fn go_forward(steps: i32) {
-        util::history().go_with_delta(steps).expect("go forward");
+        routing::go_in_current_history(steps);
     }
 
     /// If the current `Url`'s path prefix is equal to `path_base`,
@@ -416,6 +457,34 @@ impl Url {
     pub fn invalid_components_mut(&mut self) -> &mut Vec<String> {
         &mut self.invalid_components
     }
+
+    /// Attach the value of the history entry - see `subs::UrlChanged::state`.
+    pub(crate) fn with_history_state(mut self, state: Option<serde_json::Value>) -> Self {
+        self.history_state = state;
+        self
+    }
+
+    /// The value of the history entry the url has been read from.
+    pub(crate) const fn history_state(&self) -> Option<&serde_json::Value> {
+        self.history_state.as_ref()
+    }
+}
+
+impl Url {
+    /// Link `href` for the app's `routing_mode` - the url is rendered as a hash
+    /// (`#/path?search#hash`) in `RoutingMode::Hash`. Other modes use `Url::to_string`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///a![attrs!{At::Href => Urls::new(base_url).report().to_href(RoutingMode::Hash)}, "Report"]
+    /// ```
+    pub fn to_href(&self, routing_mode: RoutingMode) -> String {
+        match routing_mode {
+            RoutingMode::Hash => format!("#{}", self),
+            RoutingMode::Path | RoutingMode::Memory => self.to_string(),
+        }
+    }
 }
 
 /// `Url` components are automatically encoded.
@@ -547,10 +616,15 @@ impl From<&web_sys::Url> for Url {
             hash,
             search,
             invalid_components,
+            history_state: None,
         }
     }
 }
 
+fn to_state_value(state: &impl Serialize) -> serde_json::Value {
+    serde_json::to_value(state).expect("Problem serializing history state")
+}
+
 // ------ UrlSearch ------
 
 #[allow(clippy::module_name_repetitions)]
@@ -736,6 +810,21 @@ mod tests {
         assert_eq!(expected, actual)
     }
 
+    #[wasm_bindgen_test]
+    fn href_in_routing_modes() {
+        let url: Url = "/admin/report?week=3#intro".parse().unwrap();
+        assert_eq!(url.to_string(), "/admin/report?week=3#intro");
+        assert_eq!(url.to_href(RoutingMode::Path), "/admin/report?week=3#intro");
+        assert_eq!(
+            url.to_href(RoutingMode::Memory),
+            "/admin/report?week=3#intro"
+        );
+        assert_eq!(
+            url.to_href(RoutingMode::Hash),
+            "#/admin/report?week=3#intro"
+        );
+    }
+
     #[wasm_bindgen_test]
     fn parse_url_path() {
         let expected = Url::new().set_path(&["path1", "path2"]);
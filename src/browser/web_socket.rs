@@ -1,7 +1,10 @@
 use crate::app::Orders;
+use futures::channel::mpsc;
+use futures::stream::{Stream, StreamExt};
 use gloo_file::FileReadError;
 use serde::Serialize;
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::MessageEvent;
 
 mod builder;
 mod message;
@@ -256,6 +259,34 @@ impl WebSocket {
     }
 }
 
+/// Stream messages received by a new Web Socket.
+/// The connection is closed when the stream is dropped - see `Subs::web_socket`.
+pub(crate) fn message_stream(url: &str) -> Result<impl Stream<Item = WebSocketMessage>> {
+    let (sender, receiver) = mpsc::unbounded();
+
+    // @TODO replace with `Closure::new` once stable.
+    let on_message = Closure::wrap(Box::new(move |message_event: MessageEvent| {
+        let message = WebSocketMessage {
+            data: message_event.data(),
+            message_event,
+        };
+        // The receiver could have been dropped.
+        let _ = sender.unbounded_send(message);
+    }) as Box<dyn Fn(MessageEvent)>);
+
+    let callbacks = Callbacks {
+        on_message: Some(on_message),
+        ..Callbacks::default()
+    };
+    let web_socket = WebSocket::new(url, callbacks, &[], None)?;
+
+    // Move `web_socket` into the stream to keep the connection open.
+    Ok(receiver.map(move |message| {
+        let _ = &web_socket;
+        message
+    }))
+}
+
 impl Drop for WebSocket {
     fn drop(&mut self) {
         if matches!(self.state(), State::Connecting | State::Open) {
@@ -1,5 +1,5 @@
 use super::{BinaryType, CloseEvent, Result, WebSocket, WebSocketMessage};
-use crate::app::Orders;
+use crate::app::{HandlerOutput, Orders};
 use std::marker::PhantomData;
 use std::rc::Rc;
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
@@ -75,17 +75,12 @@ impl<'a, U: AsRef<str>, Ms: 'static, O: Orders<Ms>> Builder<'a, U, Ms, O> {
     /// to `State::Open`; this indicates that the connection is ready to send and receive data.
     ///
     /// [MDN reference](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/onopen)
-    pub fn on_open<MsU: 'static>(
+    pub fn on_open<MsU: HandlerOutput<Ms, Kind>, Kind>(
         mut self,
         handler: impl FnOnce() -> MsU + Clone + 'static,
     ) -> Self {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(JsValue) -> Option<Ms>,
-            // The event is generic - doesn't contain any useful information.
-            |_| handler.clone()(),
-            "WebSocket handler on_open can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        // The event is generic - doesn't contain any useful information.
+        let handler = Rc::new(move |_: JsValue| handler.clone()().into_msg());
         let callback = create_js_handler(handler, self.orders);
         self.callbacks.on_open = Some(callback);
         self
@@ -95,16 +90,12 @@ impl<'a, U: AsRef<str>, Ms: 'static, O: Orders<Ms>> Builder<'a, U, Ms, O> {
     /// to `State::Closed`.
     ///
     /// [MDN reference](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/onclose)
-    pub fn on_close<MsU: 'static>(
+    pub fn on_close<MsU: HandlerOutput<Ms, Kind>, Kind>(
         mut self,
         handler: impl FnOnce(CloseEvent) -> MsU + Clone + 'static,
     ) -> Self {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(JsValue) -> Option<Ms>,
-            |event: JsValue| { handler.clone()(event.unchecked_into()) },
-            "WebSocket handler on_close can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        let handler =
+            Rc::new(move |event: JsValue| handler.clone()(event.unchecked_into()).into_msg());
         let callback = create_js_handler(handler, self.orders);
         self.callbacks.on_close = Some(callback);
         self
@@ -113,17 +104,12 @@ impl<'a, U: AsRef<str>, Ms: 'static, O: Orders<Ms>> Builder<'a, U, Ms, O> {
     /// Set `on_error` Web Socket handler.
     ///
     /// [MDN reference](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/onerror)
-    pub fn on_error<MsU: 'static>(
+    pub fn on_error<MsU: HandlerOutput<Ms, Kind>, Kind>(
         mut self,
         handler: impl FnOnce() -> MsU + Clone + 'static,
     ) -> Self {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(JsValue) -> Option<Ms>,
-            // The event is generic - doesn't contain any useful information.
-            |_| handler.clone()(),
-            "WebSocket handler on_error can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        // The event is generic - doesn't contain any useful information.
+        let handler = Rc::new(move |_: JsValue| handler.clone()().into_msg());
         let callback = create_js_handler(handler, self.orders);
         self.callbacks.on_error = Some(callback);
         self
@@ -133,22 +119,17 @@ impl<'a, U: AsRef<str>, Ms: 'static, O: Orders<Ms>> Builder<'a, U, Ms, O> {
     /// from the server.
     ///
     /// [MDN reference](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/onmessage)
-    pub fn on_message<MsU: 'static>(
+    pub fn on_message<MsU: HandlerOutput<Ms, Kind>, Kind>(
         mut self,
         handler: impl FnOnce(WebSocketMessage) -> MsU + Clone + 'static,
     ) -> Self {
-        let handler = map_callback_return_to_option_ms!(
-            dyn Fn(MessageEvent) -> Option<Ms>,
-            |message_event: MessageEvent| {
-                let message = WebSocketMessage {
-                    data: message_event.data(),
-                    message_event,
-                };
-                handler.clone()(message)
-            },
-            "WebSocket handler on_message can return only Msg, Option<Msg> or ()!",
-            Rc
-        );
+        let handler = Rc::new(move |message_event: MessageEvent| {
+            let message = WebSocketMessage {
+                data: message_event.data(),
+                message_event,
+            };
+            handler.clone()(message).into_msg()
+        });
         let callback = create_js_handler(handler, self.orders);
         self.callbacks.on_message = Some(callback);
         self
@@ -58,36 +58,6 @@
 )]
 #![allow(deprecated)]
 
-// @TODO Refactor once `optin_builtin_traits` or `negative_impls`
-// @TODO is stable (https://github.com/seed-rs/seed/issues/391).
-// --
-// @TODO Remove `'static` bound from all `MsU`s once `optin_builtin_traits`, `negative_impls`
-// @TODO or https://github.com/rust-lang/rust/issues/41875 is stable.
-macro_rules! map_callback_return_to_option_ms {
-    ($cb_type:ty, $callback:expr, $panic_text:literal, $output_type:tt) => {{
-        let t_type = std::any::TypeId::of::<MsU>();
-        if t_type == std::any::TypeId::of::<Ms>() {
-            $output_type::new(move |value| {
-                (&mut Some($callback(value)) as &mut dyn std::any::Any)
-                    .downcast_mut::<Option<Ms>>()
-                    .and_then(Option::take)
-            })
-        } else if t_type == std::any::TypeId::of::<Option<Ms>>() {
-            $output_type::new(move |value| {
-                (&mut $callback(value) as &mut dyn std::any::Any)
-                    .downcast_mut::<Option<Ms>>()
-                    .and_then(Option::take)
-            })
-        } else if t_type == std::any::TypeId::of::<()>() {
-            $output_type::new(move |value| {
-                $callback(value);
-                None
-            }) as $output_type<$cb_type>
-        } else {
-            panic!($panic_text);
-        }
-    }};
-}
 // @TODO move to prelude (?)
 pub use crate::{
     app::App,
@@ -114,6 +84,8 @@ pub mod app;
 pub mod browser;
 pub mod dom_entity_names;
 pub mod helpers;
+pub mod router;
+pub mod testing;
 pub mod virtual_dom;
 
 /// Create an element flagged in a way that it will not be rendered. Useful
@@ -170,13 +142,15 @@ pub fn set_timeout(handler: Box<dyn Fn()>, timeout: i32) {
 pub mod prelude {
     pub use crate::{
         app::{
-            cmds, streams, subs, App, CmdHandle, GetElement, MessageMapper, Orders, RenderInfo,
-            StreamHandle, SubHandle,
+            cmds, component, streams, subs, App, CmdHandle, Component, ComponentMsg, GetElement,
+            HandlerOutput, MessageMapper, Navigation, NavigationGuard, Orders, RenderInfo,
+            ScrollRestoration, StreamHandle, SubHandle, Subs,
         },
         browser::dom::css_units::*,
         browser::dom::event_handler::{
             drag_ev, ev, input_ev, keyboard_ev, mouse_ev, pointer_ev, raw_ev, simple_ev, touch_ev,
         },
+        browser::dom::Stylesheet,
         browser::fetch::{self, fetch, FetchError, Header, Method, Request, Response, Status},
         browser::util::{
             request_animation_frame, ClosureNew, RequestAnimationFrameHandle,
@@ -184,14 +158,16 @@ pub mod prelude {
         },
         browser::web_socket::{self, CloseEvent, WebSocket, WebSocketError, WebSocketMessage},
         browser::web_storage::{self, LocalStorage, SessionStorage, WebStorage},
-        browser::{Url, UrlSearch},
+        browser::{RoutingMode, Url, UrlSearch},
         helpers::not,
+        router::Routable,
         // macros are exported in crate root
         // https://github.com/rust-lang-nursery/reference/blob/master/src/macros-by-example.md
         shortcuts::*,
         virtual_dom::{
-            el_key, el_ref::el_ref, AsAtValue, At, AtValue, CSSValue, El, ElRef, Ev, EventHandler,
-            IntoNodes, Node, St, Tag, ToClasses, UpdateEl, UpdateElForIterator, View,
+            el_key, el_ref::el_ref, keyed_lazy, lazy, on_insert, on_remove, on_update, portal,
+            AsAtValue, At, AtValue, CSSValue, El, ElRef, Ev, EventHandler, IntoNodes, Node, St,
+            Tag, ToClasses, UpdateEl, UpdateElForIterator, View,
         },
     };
     pub use indexmap::IndexMap; // for attrs and style to work.
@@ -0,0 +1,665 @@
+//! Typed routing - map `Url`s onto your route enum and back.
+//!
+//! Routes are described by patterns like `/users/:id/posts?page`:
+//! - `users` and `posts` are static path parts.
+//! - `:id` is a path parameter - it matches any path part.
+//! - `page` after `?` is an optional search (query) parameter.
+//!
+//! Parameters are parsed by `Params::get` through `FromStr` - a route doesn't match
+//! when its handler returns `None` so the next route is tried.
+//!
+//! Each route also describes how to get its parameters back from the route, so `Url`s are
+//! created from the same patterns (see `Routable::to_url`).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//!#[derive(Debug, Clone, PartialEq)]
+//!enum Route {
+//!    Home,
+//!    UserPosts { id: u32, page: Option<u32> },
+//!    Admin(page::admin::Route),
+//!}
+//!
+//!impl Routable for Route {
+//!    fn router() -> Router<Self> {
+//!        Router::new()
+//!            .constant("/", Self::Home)
+//!            .route(
+//!                "/users/:id/posts?page",
+//!                |params| Some(Self::UserPosts { id: params.get("id")?, page: params.get("page") }),
+//!                |route| match route {
+//!                    Self::UserPosts { id, page } => {
+//!                        Some(Params::new().add("id", id).add_optional("page", page.as_ref()))
+//!                    }
+//!                    _ => None,
+//!                },
+//!            )
+//!            .nested("/admin", Self::Admin, |route| match route {
+//!                Self::Admin(route) => Some(route),
+//!                _ => None,
+//!            })
+//!    }
+//!}
+//!
+//!fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
+//!    orders.subscribe(router::url_changed(Msg::RouteChanged));
+//!    Model { route: Route::from_url(&url) }
+//!}
+//!
+//!// `update(... Msg::RouteChanged(route) =>` where `route` is `Option<Route>`.
+//!// `a![attrs!{At::Href => Route::Home.to_url()}, "Home"]`
+//! ```
+
+use crate::app::subs;
+use crate::browser::{Url, UrlSearch};
+use std::{
+    any::{self, Any, TypeId},
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::Rc,
+    str::FromStr,
+};
+
+thread_local! {
+    /// Routers created by `Routable::router` - see `router`.
+    static ROUTERS: RefCell<BTreeMap<TypeId, Rc<dyn Any>>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+// ------ Routable ------
+
+/// Route enum that can be parsed from `Url` and converted back - see the module documentation.
+pub trait Routable: Sized + 'static {
+    /// Routes of the enum in the matching order.
+    /// The router is created once and cached - see `router::router`.
+    fn router() -> Router<Self>;
+
+    /// Create `Url` by the first route that returns parameters for `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no route returns parameters for `self` or a path parameter is missing.
+    fn to_url(&self) -> Url {
+        router::<Self>().url(self).unwrap_or_else(|| {
+            panic!(
+                "no route of `{}` creates url for the given route",
+                any::type_name::<Self>()
+            )
+        })
+    }
+
+    /// Parse the remaining path parts and the search of `url`.
+    /// Returns `None` when no route matches.
+    fn from_url(url: &Url) -> Option<Self> {
+        router::<Self>().parse(url)
+    }
+
+    /// `to_url` prefixed by the path of `base_url` - useful for links when your app has
+    /// a base path (see `Url::to_base_url`).
+    fn to_url_with_base(&self, base_url: &Url) -> Url {
+        join(base_url, &self.to_url())
+    }
+}
+
+/// The router of `R` - `R::router()` is called only once per thread.
+pub fn router<R: Routable>() -> Rc<Router<R>> {
+    let type_id = TypeId::of::<R>();
+    let cached = ROUTERS.with(|routers| routers.borrow().get(&type_id).cloned());
+    if let Some(router) = cached.and_then(|router| router.downcast().ok()) {
+        return router;
+    }
+    // Create it outside of `borrow_mut` - nested routers are cached too.
+    let router = Rc::new(R::router());
+    let cached: Rc<dyn Any> = Rc::clone(&router) as _;
+    ROUTERS.with(|routers| routers.borrow_mut().insert(type_id, cached));
+    router
+}
+
+// ------ Router ------
+
+type ParseHandler<R> = Box<dyn Fn(&[&str], &UrlSearch, &Params) -> Option<R>>;
+/// Returns the route's `Url` and its parameters.
+type UrlHandler<R> = Box<dyn Fn(&Pattern, &R) -> Option<(Url, Params)>>;
+
+struct Route<R> {
+    pattern: Pattern,
+    parse: ParseHandler<R>,
+    url: UrlHandler<R>,
+}
+
+/// Ordered list of route patterns with their handlers. The first matching route wins.
+pub struct Router<R> {
+    routes: Vec<Route<R>>,
+}
+
+impl<R: 'static> Default for Router<R> {
+    fn default() -> Self {
+        Self { routes: Vec::new() }
+    }
+}
+
+impl<R: 'static> Router<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a route. The pattern has to match the whole path.
+    ///
+    /// - `parse` creates the route from the parameters.
+    /// - `to_params` returns the parameters of the route, or `None` when the route
+    ///   belongs to another pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the pattern is invalid - see `Pattern::new`.
+    pub fn route(
+        mut self,
+        pattern: &str,
+        parse: impl Fn(&Params) -> Option<R> + 'static,
+        to_params: impl Fn(&R) -> Option<Params> + 'static,
+    ) -> Self {
+        let parse = move |rest: &[&str], _: &UrlSearch, params: &Params| {
+            if rest.is_empty() {
+                parse(params)
+            } else {
+                None
+            }
+        };
+        let url = move |pattern: &Pattern, route: &R| {
+            let params = to_params(route)?;
+            Some((pattern.url(&params), params))
+        };
+        self.routes.push(Route {
+            pattern: Pattern::new(pattern),
+            parse: Box::new(parse),
+            url: Box::new(url),
+        });
+        self
+    }
+
+    /// Add a route without parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the pattern is invalid - see `Pattern::new`.
+    pub fn constant(self, pattern: &str, route: R) -> Self
+    where
+        R: Clone + PartialEq,
+    {
+        let expected = route.clone();
+        self.route(
+            pattern,
+            move |_| Some(route.clone()),
+            move |route| {
+                if route == &expected {
+                    Some(Params::new())
+                } else {
+                    None
+                }
+            },
+        )
+    }
+
+    /// Add routes of the child route enum (e.g. your page module's `Route`) under `prefix`.
+    /// The remaining path is parsed by the child's router and the result is wrapped by `wrap`.
+    /// `unwrap` returns the child route for `Routable::to_url`.
+    ///
+    /// Parameters from `prefix` are also available to the child's handlers
+    /// and they are read from the child's parameters when the `Url` is created.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the pattern is invalid - see `Pattern::new`.
+    pub fn nested<C: Routable>(
+        self,
+        prefix: &str,
+        wrap: impl Fn(C) -> R + 'static,
+        unwrap: impl Fn(&R) -> Option<&C> + 'static,
+    ) -> Self {
+        self.nested_router(prefix, router::<C>(), wrap, unwrap)
+    }
+
+    /// Like `nested`, but with an explicit child router.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the pattern is invalid - see `Pattern::new`.
+    pub fn nested_router<C: 'static>(
+        mut self,
+        prefix: &str,
+        child: Rc<Router<C>>,
+        wrap: impl Fn(C) -> R + 'static,
+        unwrap: impl Fn(&R) -> Option<&C> + 'static,
+    ) -> Self {
+        let parse = {
+            let child = Rc::clone(&child);
+            move |rest: &[&str], search: &UrlSearch, params: &Params| {
+                child.parse_parts(rest, search, params).map(&wrap)
+            }
+        };
+        let url = move |pattern: &Pattern, route: &R| {
+            let (child_url, params) = child.url_with_params(unwrap(route)?)?;
+            Some((join(&pattern.url(&params), &child_url), params))
+        };
+        self.routes.push(Route {
+            pattern: Pattern::new(prefix),
+            parse: Box::new(parse),
+            url: Box::new(url),
+        });
+        self
+    }
+
+    /// Parse the remaining path parts and the search of `url`.
+    /// Returns `None` when no route matches.
+    pub fn parse(&self, url: &Url) -> Option<R> {
+        let mut url = url.clone();
+        let search = url.search().clone();
+        self.parse_parts(&url.remaining_path_parts(), &search, &Params::new())
+    }
+
+    /// Create `Url` by the first route that returns parameters for `route`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a path parameter is missing.
+    pub fn url(&self, route: &R) -> Option<Url> {
+        self.url_with_params(route).map(|(url, _)| url)
+    }
+
+    fn parse_parts(&self, path: &[&str], search: &UrlSearch, params: &Params) -> Option<R> {
+        self.routes.iter().find_map(|route| {
+            let mut params = params.clone();
+            let rest = route.pattern.match_prefix(path, search, &mut params)?;
+            (route.parse)(rest, search, &params)
+        })
+    }
+
+    fn url_with_params(&self, route: &R) -> Option<(Url, Params)> {
+        self.routes
+            .iter()
+            .find_map(|Route { pattern, url, .. }| url(pattern, route))
+    }
+}
+
+// ------ Pattern ------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+}
+
+/// Parsed route pattern like `/users/:id/posts?page` - see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+    search_keys: Vec<String>,
+}
+
+impl Pattern {
+    /// # Panics
+    ///
+    /// Panics when a parameter doesn't have a name (e.g. `/users/:`).
+    pub fn new(pattern: &str) -> Self {
+        let (path, search) = pattern.find('?').map_or((pattern, ""), |index| {
+            (&pattern[..index], &pattern[index + 1..])
+        });
+        let segments = path
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                part.strip_prefix(':').map_or_else(
+                    || Segment::Static(part.to_owned()),
+                    |name| {
+                        assert!(
+                            !name.is_empty(),
+                            "unnamed parameter in pattern `{}`",
+                            pattern
+                        );
+                        Segment::Param(name.to_owned())
+                    },
+                )
+            })
+            .collect();
+        let search_keys = search
+            .split('&')
+            .filter(|key| !key.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+        Self {
+            segments,
+            search_keys,
+        }
+    }
+
+    /// Match the beginning of `path` and collect parameters into `params`.
+    /// Returns the unmatched path parts.
+    fn match_prefix<'a, 'b>(
+        &self,
+        path: &'a [&'b str],
+        search: &UrlSearch,
+        params: &mut Params,
+    ) -> Option<&'a [&'b str]> {
+        if path.len() < self.segments.len() {
+            return None;
+        }
+        for (segment, part) in self.segments.iter().zip(path) {
+            match segment {
+                Segment::Static(expected) if expected == part => (),
+                Segment::Static(_) => return None,
+                Segment::Param(name) => params.insert(name, part),
+            }
+        }
+        for key in &self.search_keys {
+            if let Some(value) = search.get(key).and_then(|values| values.first()) {
+                params.insert(key, value);
+            }
+        }
+        Some(&path[self.segments.len()..])
+    }
+
+    /// Create `Url` from the pattern. Search parameters missing in `params` are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a path parameter is missing in `params`.
+    pub fn url(&self, params: &Params) -> Url {
+        let path = self.segments.iter().map(|segment| match segment {
+            Segment::Static(part) => part.as_str(),
+            Segment::Param(name) => params
+                .get_str(name)
+                .unwrap_or_else(|| panic!("missing route parameter `{}`", name)),
+        });
+        let mut url = Url::new().set_path(path);
+        for key in &self.search_keys {
+            if let Some(value) = params.get_str(key) {
+                url.search_mut().push_value(key.as_str(), value.to_owned());
+            }
+        }
+        url
+    }
+}
+
+/// Append the path of `child` to the path of `base` and use search and hash of `child`.
+fn join(base: &Url, child: &Url) -> Url {
+    let url = Url::new()
+        .set_path(base.path().iter().chain(child.path()))
+        .set_search(child.search().clone());
+    match child.hash() {
+        Some(hash) => url.set_hash(hash.as_str()),
+        None => url,
+    }
+}
+
+// ------ Params ------
+
+/// Route parameters - values of path parameters and search parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the parameter and return updated `Params`.
+    pub fn add(mut self, name: impl Into<String>, value: &impl ToString) -> Self {
+        self.values.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// Add the parameter if `value` is `Some` and return updated `Params`.
+    pub fn add_optional(self, name: impl Into<String>, value: Option<&impl ToString>) -> Self {
+        match value {
+            Some(value) => self.add(name, value),
+            None => self,
+        }
+    }
+
+    /// Parse the parameter. Returns `None` when it's missing or it can't be parsed.
+    pub fn get<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get_str(name)?.parse().ok()
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    fn insert(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_owned(), value.to_owned());
+    }
+}
+
+// ------ url_changed ------
+
+/// Convert the `subs::UrlChanged` handler to the handler of the parsed route.
+/// The route is `None` when no route matches.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.subscribe(router::url_changed(Msg::RouteChanged));
+///...
+///update(... Msg::RouteChanged(Some(route)) =>
+/// ```
+pub fn url_changed<R: Routable, Out>(
+    handler: impl FnOnce(Option<R>) -> Out + Clone + 'static,
+) -> impl FnOnce(subs::UrlChanged) -> Out + Clone + 'static {
+    move |subs::UrlChanged(url)| handler(R::from_url(&url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum ReportRoute {
+        Daily,
+        Weekly,
+    }
+
+    impl Routable for ReportRoute {
+        fn router() -> Router<Self> {
+            ROUTER_CALLS.with(|calls| calls.set(calls.get() + 1));
+            Router::new()
+                .constant("/", Self::Daily)
+                .constant("/weekly", Self::Weekly)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Route {
+        Home,
+        UserPosts { id: u32, page: Option<u32> },
+        User { name: String },
+        Report(ReportRoute),
+        Unroutable,
+    }
+
+    impl Routable for Route {
+        fn router() -> Router<Self> {
+            Router::new()
+                .constant("/", Self::Home)
+                .route(
+                    "/users/:id/posts?page",
+                    |params| {
+                        Some(Self::UserPosts {
+                            id: params.get("id")?,
+                            page: params.get("page"),
+                        })
+                    },
+                    |route| match route {
+                        Self::UserPosts { id, page } => Some(
+                            Params::new()
+                                .add("id", id)
+                                .add_optional("page", page.as_ref()),
+                        ),
+                        _ => None,
+                    },
+                )
+                .route(
+                    "/users/:name/posts",
+                    |params| {
+                        Some(Self::User {
+                            name: params.get("name")?,
+                        })
+                    },
+                    |route| match route {
+                        Self::User { name } => Some(Params::new().add("name", name)),
+                        _ => None,
+                    },
+                )
+                .nested("/report", Self::Report, |route| match route {
+                    Self::Report(route) => Some(route),
+                    _ => None,
+                })
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TeamRoute {
+        Members { team: u32 },
+    }
+
+    impl Routable for TeamRoute {
+        fn router() -> Router<Self> {
+            Router::new().route(
+                "/members",
+                |params| {
+                    Some(Self::Members {
+                        team: params.get("team")?,
+                    })
+                },
+                |Self::Members { team }| Some(Params::new().add("team", team)),
+            )
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TeamsRoute(TeamRoute);
+
+    impl Routable for TeamsRoute {
+        fn router() -> Router<Self> {
+            Router::new().nested("/teams/:team", Self, |Self(route)| Some(route))
+        }
+    }
+
+    thread_local! {
+        static ROUTER_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn url_with_page(path: &[&str], page: &str) -> Url {
+        let mut url = Url::new().set_path(path);
+        url.search_mut().push_value("page", page.to_owned());
+        url
+    }
+
+    #[test]
+    fn parse_routes() {
+        assert_eq!(Route::from_url(&Url::new()), Some(Route::Home));
+        assert_eq!(
+            Route::from_url(&url_with_page(&["users", "5", "posts"], "2")),
+            Some(Route::UserPosts {
+                id: 5,
+                page: Some(2)
+            })
+        );
+        // `id` isn't `u32` - the next route matches.
+        assert_eq!(
+            Route::from_url(&Url::new().set_path(["users", "john", "posts"])),
+            Some(Route::User {
+                name: "john".to_owned()
+            })
+        );
+        assert_eq!(
+            Route::from_url(&Url::new().set_path(["report", "weekly"])),
+            Some(Route::Report(ReportRoute::Weekly))
+        );
+        assert_eq!(
+            Route::from_url(&Url::new().set_path(["report"])),
+            Some(Route::Report(ReportRoute::Daily))
+        );
+        assert_eq!(Route::from_url(&Url::new().set_path(["users", "5"])), None);
+        assert_eq!(
+            Route::from_url(&Url::new().set_path(["report", "monthly"])),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_remaining_path_parts() {
+        let mut url = Url::new().set_path(["base", "report", "weekly"]);
+        url.next_path_part();
+        assert_eq!(
+            Route::from_url(&url),
+            Some(Route::Report(ReportRoute::Weekly))
+        );
+    }
+
+    #[test]
+    fn reverse_routing() {
+        let routes = vec![
+            Route::Home,
+            Route::UserPosts { id: 5, page: None },
+            Route::UserPosts {
+                id: 5,
+                page: Some(2),
+            },
+            Route::User {
+                name: "john".to_owned(),
+            },
+            Route::Report(ReportRoute::Weekly),
+        ];
+        for route in routes {
+            assert_eq!(Route::from_url(&route.to_url()), Some(route));
+        }
+
+        assert_eq!(
+            Route::UserPosts {
+                id: 5,
+                page: Some(2)
+            }
+            .to_url(),
+            url_with_page(&["users", "5", "posts"], "2")
+        );
+        assert_eq!(
+            Route::Report(ReportRoute::Weekly)
+                .to_url_with_base(&Url::new().set_path(["base"]))
+                .path(),
+            ["base", "report", "weekly"]
+        );
+    }
+
+    #[test]
+    fn nested_route_with_prefix_parameters() {
+        let route = TeamsRoute(TeamRoute::Members { team: 7 });
+        let url = route.to_url();
+        assert_eq!(url.path(), ["teams", "7", "members"]);
+        assert_eq!(TeamsRoute::from_url(&url), Some(route));
+    }
+
+    #[test]
+    fn router_is_created_once() {
+        for _ in 0..3 {
+            ReportRoute::from_url(&Url::new());
+            ReportRoute::Weekly.to_url();
+            Route::from_url(&Url::new().set_path(["report"]));
+        }
+        assert_eq!(ROUTER_CALLS.with(Cell::get), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "no route of")]
+    fn route_without_url() {
+        Route::Unroutable.to_url();
+    }
+
+    #[test]
+    #[should_panic(expected = "missing route parameter `id`")]
+    fn missing_path_parameter() {
+        Pattern::new("/users/:id").url(&Params::new());
+    }
+}
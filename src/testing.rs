@@ -0,0 +1,807 @@
+//! Headless testing of `init`, `update` and `view`.
+//!
+//! `TestOrders` implements `Orders` without a running `App` - it only records what has been
+//! requested (messages, notifications, cmds, streams, subscriptions, render requests, ...),
+//! so you can test your app logic natively with `cargo test`.
+//!
+//! _Note:_ `TestOrders` doesn't have any `App`, so `Orders::clone_app` panics.
+//! Functions created by `Orders::msg_sender` record their messages like `send_msg`
+//! and `Orders::clone_base_path` returns the path set by `TestOrders::set_base_path`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! #[test]
+//! fn increment() {
+//!     let mut model = Model::default();
+//!     let orders = testing::run_msgs(&mut model, vec![Msg::Increment, Msg::Increment], update);
+//!
+//!     assert_eq!(model.counter, 2);
+//!     assert_eq!(orders.should_render(), ShouldRender::Render);
+//!     assert_eq!(testing::render_view(view, &model), r#"<div class="counter">2</div>"#);
+//! }
+//! ```
+
+use crate::app::cmd_manager::KeyedCmds;
+use crate::app::context::ContextStore;
+use crate::app::orders::MappedOrders;
+use crate::app::stream_manager::StreamManager;
+use crate::app::sub_manager::{Propagation, SubManager};
+use crate::app::{
+    subs, App, CmdHandle, HandlerOutput, Notification, Orders, OrdersProxy, RenderInfo,
+    ShouldRender, StreamHandle, SubHandle,
+};
+use crate::browser::Url;
+use crate::virtual_dom::{render_to_string, IntoNodes, Node};
+use futures::future::{self, abortable, FutureExt, LocalBoxFuture};
+use futures::stream::{LocalBoxStream, Stream, StreamExt};
+use serde::Serialize;
+use std::cell::{Ref, RefCell, RefMut};
+use std::{any::Any, collections::VecDeque, future::Future, mem, rc::Rc};
+
+// ------ TestOrders ------
+
+/// `Orders` that only record requests.
+///
+/// Pass it to your `init` or `update` and then inspect what they have requested.
+#[allow(clippy::module_name_repetitions)]
+pub struct TestOrders<Ms: 'static> {
+    should_render: ShouldRender,
+    /// Shared with functions created by `msg_sender`.
+    msgs: Rc<RefCell<Vec<Ms>>>,
+    notifications: Vec<Notification>,
+    propagation: Propagation,
+    cmds: Vec<LocalBoxFuture<'static, Option<Ms>>>,
+    streams: Vec<LocalBoxStream<'static, Option<Ms>>>,
+    after_next_render_callbacks: Vec<Box<dyn FnOnce(RenderInfo) -> Option<Ms>>>,
+    sub_manager: RefCell<SubManager<Ms>>,
+    custom_events: Vec<(String, serde_json::Value)>,
+    custom_event_errors: Vec<(String, serde_json::Error)>,
+    pub(crate) context: Rc<ContextStore>,
+    pub(crate) keyed_cmds: Rc<KeyedCmds>,
+    base_path: Rc<Vec<String>>,
+}
+
+impl<Ms: 'static> Default for TestOrders<Ms> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ms: 'static> TestOrders<Ms> {
+    pub fn new() -> Self {
+        Self {
+            should_render: ShouldRender::Render,
+            msgs: Rc::default(),
+            notifications: Vec::new(),
+            propagation: Propagation::default(),
+            cmds: Vec::new(),
+            streams: Vec::new(),
+            after_next_render_callbacks: Vec::new(),
+            sub_manager: RefCell::new(SubManager::new()),
+            custom_events: Vec::new(),
+            custom_event_errors: Vec::new(),
+            context: Rc::default(),
+            keyed_cmds: Rc::default(),
+            base_path: Rc::default(),
+        }
+    }
+
+    /// Set the path returned by `Orders::clone_base_path`. It's empty by default -
+    /// like in an app without the `<base>` element.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///let orders = TestOrders::<Msg>::new().set_base_path(["admin"]);
+    /// ```
+    pub fn set_base_path(mut self, base_path: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.base_path = Rc::new(base_path.into_iter().map(|part| part.to_string()).collect());
+        self
+    }
+
+    /// The last render request (`render`, `force_render_now` or `skip`).
+    /// `ShouldRender::Render` is the default one.
+    pub const fn should_render(&self) -> ShouldRender {
+        self.should_render
+    }
+
+    /// Messages sent by `send_msg` and by functions created by `msg_sender`
+    /// - `run_msgs` passes them to `update` instead.
+    pub fn msgs(&self) -> Ref<'_, [Ms]> {
+        Ref::map(self.msgs.borrow(), Vec::as_slice)
+    }
+
+    /// Remove recorded messages, other recorded requests are kept.
+    fn take_msgs(&mut self) -> Vec<Ms> {
+        mem::take(&mut *self.msgs.borrow_mut())
+    }
+
+    /// Messages of the type `SubMs` sent by `notify`.
+    pub fn notifications<SubMs: 'static>(&self) -> Vec<&SubMs> {
+        self.notifications
+            .iter()
+            .filter_map(Notification::message::<SubMs>)
+            .collect()
+    }
+
+    /// Urls requested by `notify(subs::UrlRequested::new(url))`.
+    pub fn requested_urls(&self) -> Vec<&Url> {
+        self.notifications::<subs::UrlRequested>()
+            .into_iter()
+            .map(|subs::UrlRequested(url, _)| url)
+            .collect()
+    }
+
+    /// Whether `stop_propagation` has been called.
+    pub fn propagation_stopped(&self) -> bool {
+        self.propagation.is_stopped()
+    }
+
+    /// Clear recorded messages and notifications and reset the render request
+    /// and the propagation.
+    /// Cmds, streams, subscriptions and `after_next_render` callbacks are kept.
+    pub fn clear(&mut self) {
+        self.msgs.borrow_mut().clear();
+        self.notifications.clear();
+        self.should_render = ShouldRender::Render;
+        self.propagation = Propagation::default();
+    }
+
+    // --- cmds ---
+
+    /// The number of cmds performed by `perform_cmd`, `perform_cmd_with_handle`,
+    /// `perform_cmd_keyed` and `perform_cmd_debounced` and not taken yet.
+    pub fn cmd_count(&self) -> usize {
+        self.cmds.len()
+    }
+
+    /// Take performed cmds. Each cmd resolves to the message it would send to your `update`.
+    ///
+    /// _Note:_ Cmds with a dropped `CmdHandle` and keyed cmds replaced by a later cmd
+    /// with the same key resolve to `None`. Debounced cmds are recorded without the delay.
+    pub fn take_cmds(&mut self) -> Vec<LocalBoxFuture<'static, Option<Ms>>> {
+        mem::take(&mut self.cmds)
+    }
+
+    // --- streams ---
+
+    /// The number of streams started by `stream` and `stream_with_handle` and not taken yet.
+    pub fn stream_count(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Take started streams. Each stream item is the message it would send to your `update`.
+    ///
+    /// _Note:_ Streams with a dropped `StreamHandle` end.
+    pub fn take_streams(&mut self) -> Vec<LocalBoxStream<'static, Option<Ms>>> {
+        mem::take(&mut self.streams)
+    }
+
+    // --- subscriptions ---
+
+    /// Call handlers subscribed to `SubMs` (in the priority order) and return their messages.
+    ///
+    /// _Note:_ There is no `update`, so the propagation can't be stopped.
+    pub fn notify_subscribers<SubMs: 'static + Clone>(&self, message: SubMs) -> Vec<Ms> {
+        let handlers = self
+            .sub_manager
+            .borrow()
+            .notify(&Notification::new(message), &Propagation::default());
+        handlers
+            .into_iter()
+            .filter_map(|handler| handler())
+            .collect()
+    }
+
+    // --- custom events ---
+
+    /// Names and serialized details of events dispatched by `dispatch_custom_event`.
+    pub fn custom_events(&self) -> &[(String, serde_json::Value)] {
+        &self.custom_events
+    }
+
+    /// Names and serialization errors of events that haven't been dispatched
+    /// because their detail couldn't be serialized.
+    pub fn custom_event_errors(&self) -> &[(String, serde_json::Error)] {
+        &self.custom_event_errors
+    }
+
+    // --- after_next_render ---
+
+    /// The number of callbacks registered by `after_next_render` and not run yet.
+    pub fn after_next_render_count(&self) -> usize {
+        self.after_next_render_callbacks.len()
+    }
+
+    /// Run callbacks registered by `after_next_render` and return their messages.
+    pub fn run_after_next_render(&mut self, render_info: RenderInfo) -> Vec<Ms> {
+        mem::take(&mut self.after_next_render_callbacks)
+            .into_iter()
+            .filter_map(|callback| callback(render_info))
+            .collect()
+    }
+}
+
+// ------ Already mapped to `Ms` ------
+// Shared by `TestOrders` and `OrdersProxy`.
+
+impl<Ms: 'static> MappedOrders<Ms, (), Vec<Node<Ms>>> for TestOrders<Ms> {
+    fn set_should_render(&mut self, should_render: ShouldRender) {
+        self.should_render = should_render;
+    }
+
+    fn send_mapped_msg(&mut self, msg: Ms) {
+        self.msgs.borrow_mut().push(msg);
+    }
+
+    fn notify_mapped(&mut self, notification: Notification) {
+        self.notifications.push(notification);
+    }
+
+    fn perform_mapped_cmd(&mut self, cmd: LocalBoxFuture<'static, Option<Ms>>) {
+        self.cmds.push(cmd);
+    }
+
+    fn perform_mapped_cmd_with_handle(
+        &mut self,
+        cmd: LocalBoxFuture<'static, Option<Ms>>,
+    ) -> CmdHandle {
+        let (cmd, handle) = abortable(cmd);
+        // Aborted cmd resolves to `None`.
+        self.cmds
+            .push(cmd.map(|msg| msg.ok().flatten()).boxed_local());
+        CmdHandle(handle)
+    }
+
+    fn keyed_cmds(&self) -> Rc<KeyedCmds> {
+        Rc::clone(&self.keyed_cmds)
+    }
+
+    fn perform_mapped_cmd_debounced(
+        &mut self,
+        key: String,
+        _delay_ms: u32,
+        cmd: LocalBoxFuture<'static, Option<Ms>>,
+    ) {
+        // There are no timers without a browser - record the cmd without the delay.
+        self.perform_mapped_cmd_keyed(key, cmd);
+    }
+
+    fn after_next_render_mapped(&mut self, callback: Box<dyn FnOnce(RenderInfo) -> Option<Ms>>) {
+        self.after_next_render_callbacks.push(callback);
+    }
+
+    fn sub_manager(&self) -> RefMut<'_, SubManager<Ms>> {
+        self.sub_manager.borrow_mut()
+    }
+
+    fn stop_mapped_propagation(&mut self) {
+        self.propagation.stop();
+    }
+
+    fn stream_mapped(&mut self, stream: LocalBoxStream<'static, Option<Ms>>) {
+        self.streams.push(stream);
+    }
+
+    fn stream_mapped_with_handle(
+        &mut self,
+        stream: LocalBoxStream<'static, Option<Ms>>,
+    ) -> StreamHandle {
+        // `aborted` resolves only when the handle is dropped.
+        let (aborted, handle) = abortable(future::pending::<()>());
+        let stream = StreamManager::until(stream, aborted);
+        self.streams.push(stream.boxed_local());
+        StreamHandle(handle)
+    }
+
+    fn dispatch_serialized_custom_event(
+        &mut self,
+        event_name: &str,
+        detail: serde_json::Result<serde_json::Value>,
+    ) {
+        match detail {
+            Ok(detail) => self.custom_events.push((event_name.to_owned(), detail)),
+            Err(error) => self
+                .custom_event_errors
+                .push((event_name.to_owned(), error)),
+        }
+    }
+
+    fn context_store(&self) -> &ContextStore {
+        &self.context
+    }
+
+    /// # Panics
+    ///
+    /// Always panics - there is no `App` in `TestOrders`.
+    fn clone_mapped_app(&self) -> App<Ms, (), Vec<Node<Ms>>> {
+        panic!("there is no `App` in `TestOrders`")
+    }
+
+    fn mapped_msg_sender(&self) -> Rc<dyn Fn(Option<Ms>)> {
+        let msgs = Rc::clone(&self.msgs);
+        Rc::new(move |msg| msgs.borrow_mut().extend(msg))
+    }
+
+    fn mapped_base_path(&self) -> Rc<Vec<String>> {
+        Rc::clone(&self.base_path)
+    }
+}
+
+impl<Ms: 'static> Orders<Ms> for TestOrders<Ms> {
+    type AppMs = Ms;
+    type Mdl = ();
+    type INodes = Vec<Node<Ms>>;
+
+    #[allow(clippy::redundant_closure)]
+    fn proxy<ChildMs: 'static>(
+        &mut self,
+        f: impl FnOnce(ChildMs) -> Ms + 'static + Clone,
+    ) -> OrdersProxy<'_, ChildMs, Ms, (), Vec<Node<Ms>>> {
+        OrdersProxy::new_mapped(self, move |child_ms| f.clone()(child_ms))
+    }
+
+    fn render(&mut self) -> &mut Self {
+        self.should_render = ShouldRender::Render;
+        self
+    }
+
+    fn force_render_now(&mut self) -> &mut Self {
+        self.should_render = ShouldRender::ForceRenderNow;
+        self
+    }
+
+    fn skip(&mut self) -> &mut Self {
+        self.should_render = ShouldRender::Skip;
+        self
+    }
+
+    fn notify(&mut self, message: impl Any + Clone) -> &mut Self {
+        self.notifications.push(Notification::new(message));
+        self
+    }
+
+    fn send_msg(&mut self, msg: Ms) -> &mut Self {
+        self.msgs.borrow_mut().push(msg);
+        self
+    }
+
+    fn perform_cmd<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.perform_mapped_cmd(cmd.map(handler).boxed_local());
+        self
+    }
+
+    fn perform_cmd_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> CmdHandle {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.perform_mapped_cmd_with_handle(cmd.map(handler).boxed_local())
+    }
+
+    fn perform_cmd_keyed<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.perform_mapped_cmd_keyed(key.to_string(), cmd.map(handler).boxed_local());
+        self
+    }
+
+    fn perform_cmd_debounced<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        key: impl ToString,
+        delay_ms: u32,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.perform_mapped_cmd_debounced(
+            key.to_string(),
+            delay_ms,
+            cmd.map(handler).boxed_local(),
+        );
+        self
+    }
+
+    /// # Panics
+    ///
+    /// Always panics - there is no `App` in `TestOrders`.
+    fn clone_app(&self) -> App<Ms, (), Vec<Node<Ms>>> {
+        self.clone_mapped_app()
+    }
+
+    fn msg_mapper(&self) -> Rc<dyn Fn(Ms) -> Ms> {
+        Rc::new(std::convert::identity)
+    }
+
+    fn msg_sender(&self) -> Rc<dyn Fn(Option<Ms>)> {
+        self.mapped_msg_sender()
+    }
+
+    fn after_next_render<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        callback: impl FnOnce(RenderInfo) -> MsU + 'static,
+    ) -> &mut Self {
+        self.after_next_render_mapped(Box::new(move |render_info| {
+            callback(render_info).into_msg()
+        }));
+        self
+    }
+
+    fn subscribe<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> &mut Self {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        self.sub_manager()
+            .subscribe_with_priority(handler, i8::default());
+        self
+    }
+
+    fn subscribe_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> SubHandle {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        self.sub_manager()
+            .subscribe_with_handle_and_priority(handler, i8::default())
+    }
+
+    fn subscribe_with_priority<MsU: HandlerOutput<Ms, Kind>, Kind, SubMs: 'static + Clone>(
+        &mut self,
+        priority: i8,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> &mut Self {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        self.sub_manager()
+            .subscribe_with_priority(handler, priority);
+        self
+    }
+
+    fn subscribe_with_handle_and_priority<
+        MsU: HandlerOutput<Ms, Kind>,
+        Kind,
+        SubMs: 'static + Clone,
+    >(
+        &mut self,
+        priority: i8,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> SubHandle {
+        let handler = move |value: SubMs| handler.clone()(value).into_msg();
+
+        self.sub_manager()
+            .subscribe_with_handle_and_priority(handler, priority)
+    }
+
+    fn stop_propagation(&mut self) -> &mut Self {
+        self.stop_mapped_propagation();
+        self
+    }
+
+    fn stream<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> &mut Self {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.stream_mapped(stream.map(handler).boxed_local());
+        self
+    }
+
+    fn stream_with_handle<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        &mut self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> StreamHandle {
+        let handler = |msg: MsU| msg.into_msg();
+
+        self.stream_mapped_with_handle(stream.map(handler).boxed_local())
+    }
+
+    fn dispatch_custom_event(&mut self, event_name: &str, detail: impl Serialize) -> &mut Self {
+        self.dispatch_serialized_custom_event(event_name, serde_json::to_value(detail));
+        self
+    }
+
+    fn provide_context<T: 'static>(&mut self, value: T) -> &mut Self {
+        self.context.insert(value);
+        self
+    }
+
+    fn context<T: 'static>(&self) -> Option<Rc<T>> {
+        self.context.get()
+    }
+
+    fn clone_base_path(&self) -> Rc<Vec<String>> {
+        self.mapped_base_path()
+    }
+}
+
+// ------ Helpers ------
+
+/// Call `init` with `TestOrders` and return the model together with the orders.
+pub fn run_init<Ms: 'static, Mdl>(
+    url: Url,
+    init: impl FnOnce(Url, &mut TestOrders<Ms>) -> Mdl,
+) -> (Mdl, TestOrders<Ms>) {
+    let mut orders = TestOrders::new();
+    let model = init(url, &mut orders);
+    (model, orders)
+}
+
+/// The maximum number of `update` calls in `run_msgs`.
+const MAX_UPDATES: usize = 1_000;
+
+/// Call `update` with each of `msgs` in order and return orders recorded during all calls.
+///
+/// Messages sent by `orders.send_msg` are passed to `update` right after the message
+/// that has sent them - like in a running `App`.
+///
+/// # Panics
+///
+/// Panics when `update` is called more than 1000 times (e.g. two messages send each other).
+pub fn run_msgs<Ms: 'static, Mdl>(
+    model: &mut Mdl,
+    msgs: impl IntoIterator<Item = Ms>,
+    mut update: impl FnMut(Ms, &mut Mdl, &mut TestOrders<Ms>),
+) -> TestOrders<Ms> {
+    let mut orders = TestOrders::new();
+    let mut update_count = 0;
+    for msg in msgs {
+        let mut queue = VecDeque::from(vec![msg]);
+        while let Some(msg) = queue.pop_front() {
+            update_count += 1;
+            assert!(
+                update_count <= MAX_UPDATES,
+                "`run_msgs` has called `update` {} times - do messages send each other?",
+                MAX_UPDATES
+            );
+            update(msg, model, &mut orders);
+            queue.extend(orders.take_msgs());
+        }
+    }
+    orders
+}
+
+/// Render `view` into an HTML string - useful for snapshot tests.
+pub fn render_view<Ms, Mdl, INodes: IntoNodes<Ms>>(
+    view: impl FnOnce(&Mdl) -> INodes,
+    model: &Mdl,
+) -> String {
+    render_to_string(view(model))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use futures::executor::block_on;
+    use futures::future::{self, join_all};
+    use futures::stream::{self as futures_stream, StreamExt};
+
+    #[derive(Default)]
+    struct Model {
+        counter: i32,
+        sub_handle: Option<SubHandle>,
+        stream_handle: Option<StreamHandle>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Msg {
+        Increment,
+        Incremented(i32),
+        Subscribe,
+        Unsubscribe,
+        StartStream,
+        StopStream,
+        Child(ChildMsg),
+        Rendered(f64),
+        Navigate,
+        IncrementTwice,
+        Loop,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum ChildMsg {
+        Clicked,
+    }
+
+    #[derive(Clone)]
+    struct Ping;
+
+    fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Increment => {
+                model.counter += 1;
+                let counter = model.counter;
+                orders
+                    .skip()
+                    .perform_cmd(async move { Msg::Incremented(counter) });
+            }
+            Msg::Incremented(_) | Msg::Rendered(_) => (),
+            Msg::Subscribe => {
+                model.sub_handle = Some(orders.subscribe_with_handle(|Ping| Msg::Increment));
+            }
+            Msg::Unsubscribe => model.sub_handle = None,
+            Msg::StartStream => {
+                model.stream_handle = Some(
+                    orders
+                        .stream_with_handle(futures_stream::iter(vec![1, 2]).map(Msg::Incremented)),
+                );
+            }
+            Msg::StopStream => model.stream_handle = None,
+            Msg::Child(child_msg) => {
+                let mut orders = orders.proxy(Msg::Child);
+                orders
+                    .send_msg(child_msg)
+                    .perform_cmd(async { ChildMsg::Clicked })
+                    .after_next_render(|_| ChildMsg::Clicked);
+            }
+            Msg::IncrementTwice => {
+                orders.send_msg(Msg::Increment).send_msg(Msg::Increment);
+            }
+            Msg::Loop => {
+                orders.send_msg(Msg::Loop);
+            }
+            Msg::Navigate => {
+                orders
+                    .force_render_now()
+                    .notify(subs::UrlRequested::new(Url::new().set_path(["about"])))
+                    .after_next_render(|render_info| Msg::Rendered(render_info.timestamp));
+            }
+        }
+    }
+
+    fn view(model: &Model) -> Node<Msg> {
+        div![C!["counter"], model.counter]
+    }
+
+    #[test]
+    fn record_cmds_and_render() {
+        let mut model = Model::default();
+        let mut orders = run_msgs(&mut model, vec![Msg::Increment, Msg::Increment], update);
+
+        assert_eq!(model.counter, 2);
+        assert_eq!(orders.should_render(), ShouldRender::Skip);
+        assert_eq!(orders.cmd_count(), 2);
+        assert_eq!(
+            block_on(join_all(orders.take_cmds())),
+            vec![Some(Msg::Incremented(1)), Some(Msg::Incremented(2))]
+        );
+        assert_eq!(orders.cmd_count(), 0);
+        assert_eq!(render_view(view, &model), r#"<div class="counter">2</div>"#);
+    }
+
+    #[test]
+    fn run_sent_msgs() {
+        let mut model = Model::default();
+        let orders = run_msgs(
+            &mut model,
+            vec![Msg::IncrementTwice, Msg::Increment],
+            update,
+        );
+
+        assert_eq!(model.counter, 3);
+        assert!(orders.msgs().is_empty());
+        assert_eq!(orders.cmd_count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "do messages send each other?")]
+    fn run_msgs_loop() {
+        run_msgs(&mut Model::default(), vec![Msg::Loop], update);
+    }
+
+    #[test]
+    fn record_proxied_orders() {
+        let mut model = Model::default();
+        let mut orders = TestOrders::new();
+        update(Msg::Child(ChildMsg::Clicked), &mut model, &mut orders);
+
+        assert_eq!(*orders.msgs(), [Msg::Child(ChildMsg::Clicked)]);
+        assert_eq!(
+            block_on(join_all(orders.take_cmds())),
+            vec![Some(Msg::Child(ChildMsg::Clicked))]
+        );
+        assert_eq!(orders.after_next_render_count(), 1);
+    }
+
+    #[test]
+    fn msg_sender_and_base_path() {
+        let mut orders = TestOrders::new().set_base_path(["admin"]);
+        orders.msg_sender()(Some(Msg::Increment));
+        orders.proxy(Msg::Child).msg_sender()(Some(ChildMsg::Clicked));
+        orders.msg_sender()(None);
+
+        assert_eq!(
+            *orders.msgs(),
+            [Msg::Increment, Msg::Child(ChildMsg::Clicked)]
+        );
+        assert_eq!(
+            *orders.proxy(Msg::Child).clone_base_path(),
+            vec!["admin".to_owned()]
+        );
+    }
+
+    #[test]
+    fn record_notifications_and_after_next_render() {
+        let mut model = Model::default();
+        let mut orders = run_msgs(&mut model, vec![Msg::Navigate], update);
+
+        assert_eq!(orders.should_render(), ShouldRender::ForceRenderNow);
+        assert_eq!(orders.requested_urls().len(), 1);
+        assert_eq!(orders.requested_urls()[0].path(), &["about"]);
+
+        let render_info = RenderInfo {
+            timestamp: 5.,
+            timestamp_delta: None,
+        };
+        assert_eq!(
+            orders.run_after_next_render(render_info),
+            vec![Msg::Rendered(5.)]
+        );
+        assert_eq!(orders.after_next_render_count(), 0);
+
+        orders.clear();
+        assert!(orders.requested_urls().is_empty());
+        assert_eq!(orders.should_render(), ShouldRender::Render);
+    }
+
+    #[test]
+    fn subscriptions() {
+        let mut model = Model::default();
+        let mut orders = TestOrders::new();
+
+        update(Msg::Subscribe, &mut model, &mut orders);
+        assert_eq!(orders.notify_subscribers(Ping), vec![Msg::Increment]);
+
+        update(Msg::Unsubscribe, &mut model, &mut orders);
+        assert!(orders.notify_subscribers(Ping).is_empty());
+    }
+
+    #[test]
+    fn streams() {
+        let mut model = Model::default();
+        let mut orders = run_msgs(&mut model, vec![Msg::StartStream], update);
+        let stream = orders.take_streams().remove(0);
+        assert_eq!(
+            block_on(stream.collect::<Vec<_>>()),
+            vec![Some(Msg::Incremented(1)), Some(Msg::Incremented(2))]
+        );
+
+        update(Msg::StartStream, &mut model, &mut orders);
+        update(Msg::StopStream, &mut model, &mut orders);
+        let stream = orders.take_streams().remove(0);
+        assert!(block_on(stream.collect::<Vec<_>>()).is_empty());
+    }
+
+    #[test]
+    fn aborted_cmd() {
+        let mut orders = TestOrders::<Msg>::new();
+        let handle = orders.perform_cmd_with_handle(future::ready(Msg::Increment));
+        drop(handle);
+        assert_eq!(block_on(join_all(orders.take_cmds())), vec![None]);
+    }
+
+    #[test]
+    fn init() {
+        let (model, orders) = run_init(Url::new(), |_, orders: &mut TestOrders<Msg>| {
+            orders.send_msg(Msg::Increment);
+            Model::default()
+        });
+        assert_eq!(model.counter, 0);
+        assert_eq!(*orders.msgs(), [Msg::Increment]);
+    }
+}
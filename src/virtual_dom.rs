@@ -1,11 +1,13 @@
 pub mod attrs;
 pub mod el_ref;
 pub mod event_handler_manager;
+pub mod lifecycle_hook;
 pub mod mailbox;
 pub mod node;
 pub mod patch;
 pub mod style;
 pub mod to_classes;
+pub mod to_html;
 pub mod update_el;
 pub mod values;
 pub mod view;
@@ -13,10 +15,12 @@ pub mod view;
 pub use attrs::Attrs;
 pub use el_ref::{el_ref, ElRef, SharedNodeWs};
 pub use event_handler_manager::{EventHandler, EventHandlerManager, Listener};
+pub use lifecycle_hook::{on_insert, on_remove, on_update, LifecycleEvent, LifecycleHook};
 pub use mailbox::Mailbox;
-pub use node::{el_key, El, ElKey, IntoNodes, Node, Text};
+pub use node::{el_key, keyed_lazy, lazy, portal, El, ElKey, IntoNodes, Lazy, Node, Portal, Text};
 pub use style::Style;
 pub use to_classes::ToClasses;
+pub use to_html::render_to_string;
 pub use update_el::{UpdateEl, UpdateElForIterator};
 pub use values::{AsAtValue, AtValue, CSSValue};
 pub use view::View;
@@ -756,6 +760,98 @@ pub mod tests {
         }
     }
 
+    #[wasm_bindgen_test]
+    fn hydrate_reuses_matching_nodes() {
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        parent.set_inner_html(r#"<p class="a">Count: 5</p><!-- comment --><input value="x">"#);
+        let old_p = parent.first_child().expect("p");
+        let old_input = parent.last_child().expect("input");
+
+        let mut vdom: Vec<Node<Msg>> =
+            vec![p![C!["a"], "Count: ", 5], input![attrs! {At::Value => "x"}]];
+        virtual_dom_bridge::hydrate_children(&doc, &parent, &mut vdom, &mailbox);
+
+        assert_eq!(
+            parent.inner_html(),
+            r#"<p class="a">Count: 5</p><input value="x">"#
+        );
+        assert!(old_p.is_same_node(vdom[0].node_ws()));
+        assert!(old_input.is_same_node(vdom[1].node_ws()));
+        if let Node::Element(p_el) = &vdom[0] {
+            let text_nodes = iter_child_nodes(&old_p).collect::<Vec<_>>();
+            assert_eq!(text_nodes.len(), 2);
+            assert!(text_nodes[0].is_same_node(p_el.children[0].node_ws()));
+            assert!(text_nodes[1].is_same_node(p_el.children[1].node_ws()));
+        } else {
+            panic!("Node not Element")
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn hydrate_fixes_mismatches() {
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        parent.set_inner_html(r#"<ul id="x"><li>a</li><li>b</li></ul><span>c</span><b>d</b>"#);
+        let old_ul = parent.first_child().expect("ul");
+
+        let mut vdom: Vec<Node<Msg>> = vec![ul![C!["list"], li!["a"]], div!["c"]];
+        virtual_dom_bridge::hydrate_children(&doc, &parent, &mut vdom, &mailbox);
+
+        assert_eq!(
+            parent.inner_html(),
+            r#"<ul class="list"><li>a</li></ul><div>c</div>"#
+        );
+        assert!(old_ul.is_same_node(vdom[0].node_ws()));
+    }
+
+    #[wasm_bindgen_test]
+    fn portal_mounted_patched_and_removed() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let target = doc.create_element("div").expect("target");
+
+        let mut vdom = div![];
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div!["a", portal(target.clone(), span!["modal"]), "b"],
+            &app,
+        );
+        assert_eq!(parent.inner_html(), "<div>ab</div>");
+        assert_eq!(target.inner_html(), "<span>modal</span>");
+        let span = target.first_child().expect("span");
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div!["a", portal(target.clone(), span!["changed"]), "b"],
+            &app,
+        );
+        assert_eq!(target.inner_html(), "<span>changed</span>");
+        assert!(span.is_same_node(target.first_child().as_ref()));
+
+        call_patch(&doc, &parent, &mailbox, vdom, div!["a", "b"], &app);
+        assert_eq!(parent.inner_html(), "<div>ab</div>");
+        assert_eq!(target.inner_html(), "");
+    }
+
     /// Tests an update() function that repeatedly sends messages or performs commands.
     #[wasm_bindgen_test(async)]
     async fn update_promises() {
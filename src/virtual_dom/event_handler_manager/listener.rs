@@ -45,8 +45,7 @@ impl<Ms> Listener<Ms> {
                     event_handlers
                 });
                 for handler_callback in handler_callbacks {
-                    let msg = handler_callback(event.clone());
-                    mailbox.send(msg);
+                    mailbox.send_from(|| handler_callback(event.clone()));
                 }
             }),
         );
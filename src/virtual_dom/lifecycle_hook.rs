@@ -0,0 +1,177 @@
+use super::{El, Mailbox, Node};
+use crate::app::{HandlerOutput, MessageMapper};
+use std::{fmt, rc::Rc};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+
+/// Invoke `handler` when the element is inserted into the DOM.
+///
+/// It's useful for integrating third-party JS widgets (maps, editors, charts, ..)
+/// that need a DOM element to work with.
+///
+/// Handler can return `Msg`, `Option<Msg>` or `()`.
+///
+/// _Note:_ Lifecycle hooks are invoked asynchronously after the render
+/// (i.e. the element is already attached to the document).
+/// The element is inserted again when its key, tag or namespace is changed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// div![
+///     on_insert(|el| Msg::MapInserted(el)),
+///     on_remove(|el| map_destroy(&el)),
+/// ]
+/// ```
+pub fn on_insert<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
+    handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+) -> LifecycleHook<Ms> {
+    LifecycleHook::new(LifecycleEvent::Insert, handler)
+}
+
+/// Invoke `handler` when the element is patched - i.e. when the element stays in the DOM
+/// and a new `view` has been rendered.
+///
+/// See `on_insert` for more info.
+pub fn on_update<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
+    handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+) -> LifecycleHook<Ms> {
+    LifecycleHook::new(LifecycleEvent::Update, handler)
+}
+
+/// Invoke `handler` when the element (or one of its ancestors) is removed from the DOM
+/// or the app is unmounted.
+///
+/// See `on_insert` for more info.
+pub fn on_remove<Ms: 'static, MsU: HandlerOutput<Ms, Kind>, Kind>(
+    handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+) -> LifecycleHook<Ms> {
+    LifecycleHook::new(LifecycleEvent::Remove, handler)
+}
+
+// ------ LifecycleEvent ------
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    Insert,
+    Update,
+    Remove,
+}
+
+// ------ LifecycleHook ------
+
+/// Callback invoked on the element's `LifecycleEvent`.
+/// See `on_insert`, `on_update` and `on_remove`.
+pub struct LifecycleHook<Ms> {
+    pub event: LifecycleEvent,
+    pub callback: Rc<dyn Fn(web_sys::Element) -> Option<Ms>>,
+}
+
+impl<Ms: 'static> LifecycleHook<Ms> {
+    fn new<MsU: HandlerOutput<Ms, Kind>, Kind>(
+        event: LifecycleEvent,
+        handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+    ) -> Self {
+        Self {
+            event,
+            callback: Rc::new(move |element| handler.clone()(element).into_msg()),
+        }
+    }
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for LifecycleHook<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            event: self.event,
+            callback: Rc::clone(&self.callback),
+        }
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for LifecycleHook<Ms> {
+    type SelfWithOtherMs = LifecycleHook<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> LifecycleHook<OtherMs> {
+        let callback = self.callback;
+        LifecycleHook {
+            event: self.event,
+            callback: Rc::new(move |el| callback(el).map(f.clone())),
+        }
+    }
+}
+
+impl<Ms> fmt::Debug for LifecycleHook<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LifecycleHook({:?})", self.event)
+    }
+}
+
+// ------ Invoking ------
+
+/// Invoke `el`'s hooks for `event` once the current render has been finished.
+pub(crate) fn invoke_hooks<Ms: 'static>(el: &El<Ms>, event: LifecycleEvent, mailbox: &Mailbox<Ms>) {
+    let element = match el
+        .node_ws
+        .as_ref()
+        .and_then(|node_ws| node_ws.dyn_ref::<web_sys::Element>())
+    {
+        Some(element) => element,
+        None => return,
+    };
+    for hook in el.lifecycle_hooks.iter().filter(|hook| hook.event == event) {
+        let (callback, element, mailbox) =
+            (Rc::clone(&hook.callback), element.clone(), mailbox.clone());
+        // Hooks are invoked in a microtask to not interrupt patching
+        // and to let the hooks work with the whole rendered DOM.
+        spawn_local(async move {
+            mailbox.send_from(|| callback(element));
+        });
+    }
+}
+
+/// Invoke `LifecycleEvent::Remove` hooks of `el` and all its descendants.
+pub(crate) fn invoke_remove_hooks<Ms: 'static>(el: &El<Ms>, mailbox: &Mailbox<Ms>) {
+    for child in &el.children {
+        if let Node::Element(child) = child.resolved() {
+            invoke_remove_hooks(child, mailbox);
+        }
+    }
+    invoke_hooks(el, LifecycleEvent::Remove, mailbox);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::virtual_dom::LifecycleEvent;
+
+    #[derive(Debug, Clone)]
+    enum Msg {
+        Inserted,
+    }
+
+    #[test]
+    fn hooks_are_added_to_el() {
+        let node: Node<Msg> = div![
+            on_insert(|_| Msg::Inserted),
+            on_update(|_| ()),
+            on_remove(|_| None as Option<Msg>),
+        ];
+        let node = node.map_msg(|_| ());
+        let events = match node {
+            Node::Element(el) => el
+                .lifecycle_hooks
+                .iter()
+                .map(|hook| hook.event)
+                .collect::<Vec<_>>(),
+            _ => panic!("element expected"),
+        };
+        assert_eq!(
+            events,
+            vec![
+                LifecycleEvent::Insert,
+                LifecycleEvent::Update,
+                LifecycleEvent::Remove
+            ]
+        );
+    }
+}
@@ -1,25 +1,49 @@
 use std::rc::Rc;
 
+type Scope = Rc<dyn Fn(&mut dyn FnMut())>;
+
 pub struct Mailbox<Message: 'static> {
     func: Rc<dyn Fn(Option<Message>)>,
+    /// Runs event handlers in the app's scope - see `Mailbox::send_from`.
+    scope: Option<Scope>,
 }
 
 impl<Ms> Mailbox<Ms> {
     pub fn new(func: impl Fn(Option<Ms>) + 'static) -> Self {
         Mailbox {
             func: Rc::new(func),
+            scope: None,
         }
     }
 
+    /// Invoke handlers passed to `send_from` by `scope`.
+    pub(crate) fn with_scope(mut self, scope: impl Fn(&mut dyn FnMut()) + 'static) -> Self {
+        self.scope = Some(Rc::new(scope));
+        self
+    }
+
     pub fn send(&self, message: Option<Ms>) {
         (self.func)(message)
     }
+
+    /// Invoke the event handler and send its message.
+    pub(crate) fn send_from(&self, handler: impl FnOnce() -> Option<Ms>) {
+        let scope = match &self.scope {
+            Some(scope) => scope,
+            None => return self.send(handler()),
+        };
+        let mut handler = Some(handler);
+        let mut message = None;
+        scope(&mut || message = handler.take().and_then(|handler| handler()));
+        self.send(message);
+    }
 }
 
 impl<Ms> Clone for Mailbox<Ms> {
     fn clone(&self) -> Self {
         Mailbox {
             func: self.func.clone(),
+            scope: self.scope.clone(),
         }
     }
 }
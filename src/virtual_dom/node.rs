@@ -4,10 +4,14 @@ use std::borrow::Cow;
 
 pub mod el;
 pub mod into_nodes;
+pub mod lazy;
+pub mod portal;
 pub mod text;
 
 pub use el::{el_key, El, ElKey};
 pub use into_nodes::IntoNodes;
+pub use lazy::{keyed_lazy, lazy, Lazy};
+pub use portal::{portal, Portal};
 pub use text::Text;
 
 /// A component in our virtual DOM.
@@ -19,6 +23,8 @@ pub enum Node<Ms> {
     Element(El<Ms>),
     Text(Text),
     Empty,
+    Lazy(Lazy<Ms>),
+    Portal(Portal<Ms>),
 }
 
 // @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
@@ -28,6 +34,8 @@ impl<Ms> Clone for Node<Ms> {
             Self::Element(element) => Self::Element(element.clone()),
             Self::Text(text) => Self::Text(text.clone()),
             Self::Empty => Self::Empty,
+            Self::Lazy(lazy) => Self::Lazy(lazy.clone()),
+            Self::Portal(portal) => Self::Portal(portal.clone()),
         }
     }
 }
@@ -109,7 +117,8 @@ impl<Ms> Node<Ms> {
         match self {
             Node::Element(el) => el.get_text(),
             Node::Text(text) => text.text.to_string(),
-            _ => "".to_string(),
+            Node::Lazy(lazy) => lazy.node().map(Node::get_text).unwrap_or_default(),
+            Node::Empty | Node::Portal(_) => "".to_string(),
         }
     }
 
@@ -117,6 +126,7 @@ impl<Ms> Node<Ms> {
     pub fn el_key(&self) -> Option<&ElKey> {
         match self {
             Node::Element(el) => el.key.as_ref(),
+            Node::Lazy(lazy) => lazy.key.as_ref(),
             _ => None,
         }
     }
@@ -172,6 +182,11 @@ impl<Ms> Node<Ms> {
         match self {
             Node::Text(t) => t.strip_ws_node(),
             Node::Element(e) => e.strip_ws_nodes_from_self_and_children(),
+            Node::Lazy(lazy) => lazy.resolve().strip_ws_nodes_from_self_and_children(),
+            Node::Portal(portal) => portal
+                .children
+                .iter_mut()
+                .for_each(Node::strip_ws_nodes_from_self_and_children),
             Node::Empty => (),
         }
     }
@@ -188,7 +203,8 @@ impl<Ms> Node<Ms> {
             Self::Element(El { node_ws: val, .. }) | Self::Text(Text { node_ws: val, .. }) => {
                 val.as_ref()
             }
-            _ => None,
+            Self::Lazy(lazy) => lazy.node().and_then(Node::node_ws),
+            Self::Empty | Self::Portal(_) => None,
         }
     }
 }
@@ -201,6 +217,8 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Node<Ms> {
             Node::Element(el) => Node::Element(el.map_msg(f)),
             Node::Text(text) => Node::Text(text),
             Node::Empty => Node::Empty,
+            Node::Lazy(lazy) => Node::Lazy(lazy.map_msg(f)),
+            Node::Portal(portal) => Node::Portal(portal.map_msg(f)),
         }
     }
 }
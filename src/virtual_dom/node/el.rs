@@ -1,6 +1,6 @@
 use super::super::{
-    At, AtValue, Attrs, CSSValue, EventHandler, EventHandlerManager, Node, SharedNodeWs, St, Style,
-    Tag, Text,
+    At, AtValue, Attrs, CSSValue, EventHandler, EventHandlerManager, LifecycleHook, Node,
+    SharedNodeWs, St, Style, Tag, Text,
 };
 use crate::app::MessageMapper;
 use crate::browser::{
@@ -47,6 +47,7 @@ pub struct El<Ms> {
     pub node_ws: Option<web_sys::Node>,
     pub refs: Vec<SharedNodeWs>,
     pub key: Option<ElKey>,
+    pub lifecycle_hooks: Vec<LifecycleHook<Ms>>,
 }
 
 // @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
@@ -62,6 +63,7 @@ impl<Ms> Clone for El<Ms> {
             node_ws: self.node_ws.clone(),
             refs: self.refs.clone(),
             key: self.key.clone(),
+            lifecycle_hooks: self.lifecycle_hooks.clone(),
         }
     }
 }
@@ -77,6 +79,11 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for El<Ms> {
     /// There is an overhead to calling this versus keeping all messages under one type.
     /// The deeper the nested structure of children, the more time this will take to run.
     fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> El<OtherMs> {
+        let lifecycle_hooks = self
+            .lifecycle_hooks
+            .into_iter()
+            .map(|hook| hook.map_msg(f.clone()))
+            .collect();
         El {
             tag: self.tag,
             attrs: self.attrs,
@@ -91,6 +98,7 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for El<Ms> {
             event_handler_manager: self.event_handler_manager.map_msg(f),
             refs: self.refs,
             key: self.key,
+            lifecycle_hooks,
         }
     }
 }
@@ -115,6 +123,7 @@ impl<Ms> El<Ms> {
             node_ws: None,
             refs: Vec::new(),
             key: None,
+            lifecycle_hooks: Vec::new(),
         }
     }
 
@@ -0,0 +1,248 @@
+use super::{ElKey, Node};
+use crate::app::MessageMapper;
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+
+// ------ lazy ------
+
+/// Create a node that is rendered by `view` only when `input` has changed since the last render.
+///
+/// When `input` is equal to the `input` of the previous node at the same place with the same
+/// `view`, the `view` isn't called and the previous subtree (including its DOM nodes)
+/// is reused - the patch algorithm skips it completely.
+///
+/// It's useful for big, mostly static parts of your app like tables with thousands of rows.
+///
+/// _Note:_ `view` is a function pointer (not a capturing closure) so all the data used
+/// for rendering have to be in `input`.
+///
+/// _Note:_ The reused subtree keeps its original event handlers - don't wrap lazy nodes into
+/// `map_msg` with mappers capturing data that can change while `input` is the same.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// fn view_rows(rows: &Vec<Row>) -> Node<Msg> {
+///     table![rows.iter().map(view_row)]
+/// }
+///
+/// fn view(model: &Model) -> Node<Msg> {
+///     div![
+///         view_header(model),
+///         lazy(Rc::clone(&model.rows), view_rows),
+///     ]
+/// }
+/// ```
+pub fn lazy<Ms: 'static, T: PartialEq + 'static>(input: T, view: fn(&T) -> Node<Ms>) -> Node<Ms> {
+    Node::Lazy(Lazy::new(None, input, view))
+}
+
+/// The same as `lazy`, but with an `ElKey` used for matching old and new nodes
+/// - e.g. for lazy rows of a table.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// tbody![model.rows.iter().map(|row| keyed_lazy(el_key(&row.id), row.clone(), view_row))]
+/// ```
+pub fn keyed_lazy<Ms: 'static, T: PartialEq + 'static>(
+    key: ElKey,
+    input: T,
+    view: fn(&T) -> Node<Ms>,
+) -> Node<Ms> {
+    Node::Lazy(Lazy::new(Some(key), input, view))
+}
+
+// ------ Lazy ------
+
+type LazyView<Ms> = Rc<dyn Fn(&dyn Any) -> Node<Ms>>;
+
+/// Memoized node - see `lazy` and `keyed_lazy`.
+pub struct Lazy<Ms> {
+    pub(crate) key: Option<ElKey>,
+    input: Rc<dyn Any>,
+    input_eq: fn(&dyn Any, &dyn Any) -> bool,
+    view_id: usize,
+    view: LazyView<Ms>,
+    /// The node rendered by `view`. It's `None` until the node is patched or rendered.
+    node: Option<Box<Node<Ms>>>,
+}
+
+impl<Ms: 'static> Lazy<Ms> {
+    fn new<T: PartialEq + 'static>(key: Option<ElKey>, input: T, view: fn(&T) -> Node<Ms>) -> Self {
+        Self {
+            key,
+            input: Rc::new(input),
+            input_eq: |input_a, input_b| match (
+                input_a.downcast_ref::<T>(),
+                input_b.downcast_ref::<T>(),
+            ) {
+                (Some(input_a), Some(input_b)) => input_a == input_b,
+                _ => false,
+            },
+            view_id: view as usize,
+            view: Rc::new(move |input| view(input.downcast_ref().expect("downcast lazy input"))),
+            node: None,
+        }
+    }
+}
+
+impl<Ms> Lazy<Ms> {
+    /// Identifies `view` - nodes with different `view`s can't be reused.
+    pub(crate) const fn view_id(&self) -> usize {
+        self.view_id
+    }
+
+    /// The rendered node, if it has been already rendered.
+    pub fn node(&self) -> Option<&Node<Ms>> {
+        self.node.as_deref()
+    }
+
+    /// Render the node if it hasn't been rendered yet.
+    /// Nested lazy nodes are resolved too, so the returned node is never `Node::Lazy`.
+    pub(crate) fn resolve(&mut self) -> &mut Node<Ms> {
+        let (view, input) = (&self.view, &self.input);
+        self.node
+            .get_or_insert_with(|| Box::new(view(input.as_ref())))
+            .resolve_lazy()
+    }
+
+    /// See `resolve`.
+    pub(crate) fn into_resolved(mut self) -> Node<Ms> {
+        self.resolve();
+        match self.node.map(|node| *node) {
+            Some(Node::Lazy(lazy)) => lazy.into_resolved(),
+            Some(node) => node,
+            None => Node::Empty,
+        }
+    }
+
+    /// Render the node without storing it.
+    pub(crate) fn render(&self) -> Node<Ms> {
+        (self.view)(self.input.as_ref())
+    }
+
+    /// Returns `true` if `old` has been rendered by the same `view` with the same `input`.
+    pub(crate) fn can_reuse(&self, old: &Self) -> bool {
+        old.node.is_some()
+            && self.view_id == old.view_id
+            && (self.input_eq)(self.input.as_ref(), old.input.as_ref())
+    }
+
+    /// Take over the rendered node from `old`.
+    pub(crate) fn reuse(&mut self, old: Self) {
+        self.node = old.node;
+    }
+}
+
+impl<Ms> Node<Ms> {
+    /// Resolve `Node::Lazy` - see `Lazy::resolve`. Other nodes are returned as they are.
+    pub(crate) fn resolve_lazy(&mut self) -> &mut Self {
+        match self {
+            Node::Lazy(lazy) => lazy.resolve(),
+            node => node,
+        }
+    }
+
+    /// The rendered node of an already resolved `Node::Lazy`. Other nodes are returned as they are.
+    pub(crate) fn resolved(&self) -> &Self {
+        match self {
+            Node::Lazy(lazy) => lazy.node().map_or(self, Node::resolved),
+            node => node,
+        }
+    }
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for Lazy<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            input: Rc::clone(&self.input),
+            input_eq: self.input_eq,
+            view_id: self.view_id,
+            view: Rc::clone(&self.view),
+            node: self.node.clone(),
+        }
+    }
+}
+
+impl<Ms: fmt::Debug> fmt::Debug for Lazy<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("key", &self.key)
+            .field("view_id", &self.view_id)
+            .field("node", &self.node)
+            .finish()
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Lazy<Ms> {
+    type SelfWithOtherMs = Lazy<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> Lazy<OtherMs> {
+        let view = self.view;
+        let view_f = f.clone();
+        Lazy {
+            key: self.key,
+            input: self.input,
+            input_eq: self.input_eq,
+            view_id: self.view_id,
+            view: Rc::new(move |input| view(input).map_msg(view_f.clone())),
+            node: self.node.map(|node| Box::new(node.map_msg(f))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum Msg {}
+
+    fn view_counter(counter: &i32) -> Node<Msg> {
+        div![counter]
+    }
+
+    fn view_other_counter(counter: &i32) -> Node<Msg> {
+        span![counter]
+    }
+
+    fn lazy_of(node: Node<Msg>) -> Lazy<Msg> {
+        match node {
+            Node::Lazy(lazy) => lazy,
+            _ => panic!("lazy node expected"),
+        }
+    }
+
+    #[test]
+    fn reuse_only_rendered_with_equal_input_and_view() {
+        let mut old = lazy_of(lazy(1, view_counter));
+        assert!(!lazy_of(lazy(1, view_counter)).can_reuse(&old));
+
+        old.resolve();
+        assert!(lazy_of(lazy(1, view_counter)).can_reuse(&old));
+        assert!(!lazy_of(lazy(2, view_counter)).can_reuse(&old));
+        assert!(!lazy_of(lazy(1, view_other_counter)).can_reuse(&old));
+        assert!(!lazy_of(lazy(1_u8, |_| empty![])).can_reuse(&old));
+
+        let mut new = lazy_of(lazy(1, view_counter));
+        new.reuse(old);
+        assert_eq!(new.node().unwrap().to_string(), "<div>1</div>");
+    }
+
+    #[test]
+    fn resolve_nested() {
+        fn view_lazy(counter: &i32) -> Node<Msg> {
+            lazy(*counter, view_counter)
+        }
+        let mut node = lazy(5, view_lazy);
+        assert_eq!(node.resolve_lazy().to_string(), "<div>5</div>");
+        assert_eq!(
+            lazy_of(lazy(5, view_lazy)).into_resolved().to_string(),
+            "<div>5</div>"
+        );
+    }
+}
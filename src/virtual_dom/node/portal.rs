@@ -0,0 +1,120 @@
+use super::{IntoNodes, Node};
+use crate::app::{GetElement, MessageMapper};
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    rc::Rc,
+};
+use web_sys::Element;
+
+// ------ portal ------
+
+/// Render `nodes` into the `target` element instead of into the parent element.
+///
+/// It's useful for modals, tooltips or toasts that have to escape containers with
+/// `overflow: hidden` or `z-index` - the nodes are still a part of your `view`,
+/// they are patched and their events are handled like the other nodes.
+///
+/// _Note:_ `target` should be an element outside of the app's mount point,
+/// e.g. `document.body`. Nodes are appended to the element's current children.
+///
+/// `target` is resolved when the portal is rendered into the DOM, so `view` can be rendered
+/// also natively (e.g. by `Node::to_string`) - portal's nodes aren't included in the HTML.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// fn view(model: &Model) -> Node<Msg> {
+///     div![
+///         style!{St::Overflow => "hidden"},
+///         IF!(model.modal_opened => portal("modals", view_modal(model))),
+///     ]
+/// }
+/// ```
+pub fn portal<Ms>(target: impl GetElement + 'static, nodes: impl IntoNodes<Ms>) -> Node<Ms> {
+    Node::Portal(Portal {
+        target: PortalTarget::new(target),
+        children: nodes.into_nodes(),
+    })
+}
+
+// ------ Portal ------
+
+/// Nodes rendered into another element - see `portal`.
+#[derive(Debug)]
+pub struct Portal<Ms> {
+    pub(crate) target: PortalTarget,
+    pub children: Vec<Node<Ms>>,
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for Portal<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            target: self.target.clone(),
+            children: self.children.clone(),
+        }
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Portal<Ms> {
+    type SelfWithOtherMs = Portal<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> Portal<OtherMs> {
+        Portal {
+            target: self.target,
+            children: self.children.map_msg(f),
+        }
+    }
+}
+
+// ------ PortalTarget ------
+
+type GetTargetElement = Box<dyn FnOnce() -> Result<Element, String>>;
+
+/// Portal's target element. It's resolved on the first access and shared by portal's clones.
+#[derive(Clone)]
+pub(crate) struct PortalTarget(Rc<TargetState>);
+
+struct TargetState {
+    get_element: Cell<Option<GetTargetElement>>,
+    /// `None` when the element cannot be found.
+    element: RefCell<Option<Element>>,
+}
+
+impl PortalTarget {
+    fn new(target: impl GetElement + 'static) -> Self {
+        Self(Rc::new(TargetState {
+            get_element: Cell::new(Some(Box::new(move || target.get_element()))),
+            element: RefCell::new(None),
+        }))
+    }
+
+    /// The error is logged only once, the portal isn't rendered in that case.
+    pub(crate) fn element(&self) -> Option<Element> {
+        if let Some(get_element) = self.0.get_element.take() {
+            let element = get_element()
+                .map_err(|error| crate::error(format!("Cannot render portal: {}", error)))
+                .ok();
+            self.0.element.replace(element);
+        }
+        self.0.element.borrow().clone()
+    }
+}
+
+impl fmt::Debug for PortalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PortalTarget({:?})", self.0.element.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn render_portal_natively() {
+        let node: Node<()> = div!["page", portal("modals", div!["modal"])];
+        assert_eq!(node.to_string(), "<div>page</div>");
+    }
+}
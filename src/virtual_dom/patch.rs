@@ -1,9 +1,10 @@
 //! This module contains code related to patching the VDOM. It can be considered
 //! a subset of the `vdom` module.
 
-use super::{El, IntoNodes, Mailbox, Node, Text};
+use super::{lifecycle_hook, El, IntoNodes, LifecycleEvent, Mailbox, Node, Portal, Text};
 use crate::app::App;
 use crate::browser::dom::virtual_dom_bridge;
+use std::mem;
 use web_sys::Document;
 
 mod patch_gen;
@@ -51,6 +52,7 @@ fn insert_el<'a, Ms>(
         .attach_listeners(new_node.clone(), None, mailbox);
 
     new.node_ws.replace(new_node);
+    lifecycle_hook::invoke_hooks(new, LifecycleEvent::Insert, mailbox);
 }
 
 fn insert_text<'a>(
@@ -106,6 +108,7 @@ fn patch_el<'a, Ms, Mdl, INodes>(
         new_children_iter,
     );
     new.node_ws = Some(old_el_ws);
+    lifecycle_hook::invoke_hooks(new, LifecycleEvent::Update, mailbox);
 }
 
 fn patch_text(mut old: Text, new: &mut Text) {
@@ -163,6 +166,8 @@ fn replace_el_by_el<'a, Ms>(
     parent: &web_sys::Node,
     mailbox: &Mailbox<Ms>,
 ) {
+    lifecycle_hook::invoke_remove_hooks(&old, mailbox);
+    remove_portals(mem::take(&mut old.children), mailbox);
     let old_node = old
         .node_ws
         .take()
@@ -175,7 +180,10 @@ fn replace_el_by_text<'a, Ms>(
     mut old: El<Ms>,
     new: &'a mut Text,
     parent: &web_sys::Node,
+    mailbox: &Mailbox<Ms>,
 ) {
+    lifecycle_hook::invoke_remove_hooks(&old, mailbox);
+    remove_portals(mem::take(&mut old.children), mailbox);
     let old_node = old
         .node_ws
         .take()
@@ -197,10 +205,12 @@ fn replace_text_by_el<'a, Ms>(
     replace_by_el(document, &old_node, new, parent, mailbox);
 }
 
-fn remove_el<Ms>(mut old: El<Ms>, parent: &web_sys::Node) {
+fn remove_el<Ms>(mut old: El<Ms>, parent: &web_sys::Node, mailbox: &Mailbox<Ms>) {
+    lifecycle_hook::invoke_remove_hooks(&old, mailbox);
     let old_node = old.node_ws.take().expect("Missing child node_ws");
     virtual_dom_bridge::remove_node(&old_node, parent);
     old.node_ws.replace(old_node);
+    remove_portals(old.children, mailbox);
 }
 
 fn remove_text(mut old: Text, parent: &web_sys::Node) {
@@ -209,6 +219,73 @@ fn remove_text(mut old: Text, parent: &web_sys::Node) {
     old.node_ws.replace(old_node);
 }
 
+fn mount_portal<Ms>(document: &Document, new: &mut Portal<Ms>, mailbox: &Mailbox<Ms>) {
+    for child in &mut new.children {
+        virtual_dom_bridge::assign_ws_nodes(document, child);
+    }
+    virtual_dom_bridge::attach_portal(new, mailbox);
+}
+
+fn patch_portal<Ms, Mdl, INodes>(
+    document: &Document,
+    old: Portal<Ms>,
+    new: &mut Portal<Ms>,
+    mailbox: &Mailbox<Ms>,
+    app: &App<Ms, Mdl, INodes>,
+) where
+    INodes: IntoNodes<Ms>,
+{
+    let target = new.target.element();
+    if old.target.element() != target {
+        remove_portal(old, mailbox);
+        return mount_portal(document, new, mailbox);
+    }
+    if let Some(target) = target {
+        patch_els(
+            document,
+            mailbox,
+            app,
+            &target.into(),
+            old.children.into_iter(),
+            new.children.iter_mut(),
+        );
+    }
+}
+
+fn remove_portal<Ms>(old: Portal<Ms>, mailbox: &Mailbox<Ms>) {
+    // Nodes of a portal without target haven't been attached.
+    let target = match old.target.element() {
+        Some(target) => target.into(),
+        None => return,
+    };
+    for child in old.children {
+        remove_node(child, &target, mailbox);
+    }
+}
+
+fn remove_node<Ms>(old: Node<Ms>, parent: &web_sys::Node, mailbox: &Mailbox<Ms>) {
+    match old {
+        Node::Element(el) => remove_el(el, parent, mailbox),
+        Node::Text(text) => remove_text(text, parent),
+        Node::Lazy(lazy) => remove_node(lazy.into_resolved(), parent, mailbox),
+        Node::Portal(portal) => remove_portal(portal, mailbox),
+        Node::Empty => (),
+    }
+}
+
+/// Remove nodes of portals nested in removed nodes - they aren't removed together with their
+/// parents because they live in other DOM elements.
+pub(crate) fn remove_portals<Ms>(old: Vec<Node<Ms>>, mailbox: &Mailbox<Ms>) {
+    for node in old {
+        match node {
+            Node::Element(el) => remove_portals(el.children, mailbox),
+            Node::Lazy(lazy) => remove_portals(vec![lazy.into_resolved()], mailbox),
+            Node::Portal(portal) => remove_portal(portal, mailbox),
+            Node::Text(_) | Node::Empty => (),
+        }
+    }
+}
+
 pub(crate) fn patch_els<'a, Ms, Mdl, INodes, OI, NI>(
     document: &Document,
     mailbox: &Mailbox<Ms>,
@@ -243,10 +320,16 @@ pub(crate) fn patch_els<'a, Ms, Mdl, INodes, OI, NI>(
                 replace_text_by_el(document, text_old, el_new, old_el_ws, mailbox)
             }
             PatchCommand::ReplaceElByText { el_old, text_new } => {
-                replace_el_by_text(document, el_old, text_new, old_el_ws)
+                replace_el_by_text(document, el_old, text_new, old_el_ws, mailbox)
             }
-            PatchCommand::RemoveEl { el_old } => remove_el(el_old, old_el_ws),
+            PatchCommand::RemoveEl { el_old } => remove_el(el_old, old_el_ws, mailbox),
             PatchCommand::RemoveText { text_old } => remove_text(text_old, old_el_ws),
+            PatchCommand::MountPortal { portal_new } => mount_portal(document, portal_new, mailbox),
+            PatchCommand::PatchPortal {
+                portal_old,
+                portal_new,
+            } => patch_portal(document, portal_old, portal_new, mailbox, app),
+            PatchCommand::RemovePortal { portal_old } => remove_portal(portal_old, mailbox),
         };
     }
 }
@@ -274,6 +357,22 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
 
     // @TODO Do we realy need this function? This function could be replaced by calling
     // `patch_els` with `std::iter::once` for old and new nodes.
+    let old = match old {
+        Node::Lazy(lazy) => lazy.into_resolved(),
+        old => old,
+    };
+    let new = new.resolve_lazy();
+    if let (Node::Portal(_), _) | (_, Node::Portal(_)) = (&old, &new) {
+        patch_els(
+            document,
+            mailbox,
+            app,
+            parent,
+            std::iter::once(old),
+            std::iter::once(&mut *new),
+        );
+        return new.node_ws();
+    }
     match old {
         Node::Element(old_el) => match new {
             Node::Element(new_el) => {
@@ -283,8 +382,11 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
                     replace_el_by_el(document, old_el, new_el, parent, mailbox)
                 }
             }
-            Node::Text(new_text) => replace_el_by_text(document, old_el, new_text, parent),
-            Node::Empty => remove_el(old_el, parent),
+            Node::Text(new_text) => replace_el_by_text(document, old_el, new_text, parent, mailbox),
+            Node::Empty => remove_el(old_el, parent, mailbox),
+            Node::Lazy(_) | Node::Portal(_) => {
+                unreachable!("lazy nodes are resolved and portals handled")
+            }
         },
         Node::Empty => {
             match new {
@@ -304,6 +406,9 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
                 }
                 // If new and old are empty, we don't need to do anything.
                 Node::Empty => (),
+                Node::Lazy(_) | Node::Portal(_) => {
+                    unreachable!("lazy nodes are resolved and portals handled")
+                }
             }
         }
         Node::Text(old_text) => {
@@ -314,8 +419,14 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
                 }
                 Node::Empty => remove_text(old_text, parent),
                 Node::Text(new_text) => patch_text(old_text, new_text),
+                Node::Lazy(_) | Node::Portal(_) => {
+                    unreachable!("lazy nodes are resolved and portals handled")
+                }
             }
         }
+        Node::Lazy(_) | Node::Portal(_) => {
+            unreachable!("lazy nodes are resolved and portals handled")
+        }
     };
     new.node_ws()
 }
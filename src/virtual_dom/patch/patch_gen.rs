@@ -71,7 +71,7 @@
 //!
 
 use crate::browser::dom::Namespace;
-use crate::virtual_dom::{El, ElKey, Node, Tag, Text};
+use crate::virtual_dom::{El, ElKey, Node, Portal, Tag, Text};
 use std::borrow::Borrow;
 use std::collections::{BTreeSet, VecDeque};
 use std::iter::Peekable;
@@ -118,6 +118,16 @@ pub enum PatchCommand<'a, Ms: 'static> {
     RemoveText {
         text_old: Text,
     },
+    MountPortal {
+        portal_new: &'a mut Portal<Ms>,
+    },
+    PatchPortal {
+        portal_old: Portal<Ms>,
+        portal_new: &'a mut Portal<Ms>,
+    },
+    RemovePortal {
+        portal_old: Portal<Ms>,
+    },
 }
 
 /// `PatchKey` used to compare nodes during patching.
@@ -131,6 +141,10 @@ enum PatchKey {
         el_key: Option<ElKey>,
     },
     Text,
+    Lazy {
+        view_id: usize,
+        el_key: Option<ElKey>,
+    },
 }
 
 impl PatchKey {
@@ -142,7 +156,12 @@ impl PatchKey {
                 el_key: el.key.clone(),
             }),
             Node::Text(_) => Some(PatchKey::Text),
-            Node::Empty => None,
+            // Portals don't have a place among the other nodes.
+            Node::Empty | Node::Portal(_) => None,
+            Node::Lazy(lazy) => Some(PatchKey::Lazy {
+                view_id: lazy.view_id(),
+                el_key: lazy.key.clone(),
+            }),
         }
     }
 }
@@ -163,6 +182,8 @@ where
     matching_child_new: Option<NI::Item>,
     matching_key: Option<PatchKey>,
     keyed_mode: bool,
+    /// Commands that have to be yielded before the next one is generated.
+    queued_commands: VecDeque<PatchCommand<'a, Ms>>,
 }
 
 impl<'a, Ms, OI, NI> PatchGen<'a, Ms, OI, NI>
@@ -182,6 +203,7 @@ where
             matching_child_new: None,
             matching_key: None,
             keyed_mode: false,
+            queued_commands: VecDeque::new(),
         }
     }
 
@@ -359,6 +381,8 @@ where
             Node::Element(el_new) => PatchCommand::AppendEl { el_new },
             Node::Text(text_new) => PatchCommand::AppendText { text_new },
             Node::Empty => return self.next_command(),
+            Node::Lazy(lazy_new) => return self.append(lazy_new.resolve()),
+            Node::Portal(portal_new) => PatchCommand::MountPortal { portal_new },
         })
     }
 
@@ -374,6 +398,8 @@ where
                 next_node,
             },
             Node::Empty => return self.next_command(),
+            Node::Lazy(lazy_new) => return self.insert(lazy_new.resolve(), next_node),
+            Node::Portal(portal_new) => PatchCommand::MountPortal { portal_new },
         })
     }
 
@@ -382,6 +408,42 @@ where
         child_old: Node<Ms>,
         child_new: &'a mut Node<Ms>,
     ) -> Option<PatchCommand<'a, Ms>> {
+        if let (Node::Lazy(lazy_old), Node::Lazy(lazy_new)) = (&child_old, &mut *child_new) {
+            // Skip the whole subtree - it would be the same.
+            if lazy_new.can_reuse(lazy_old) {
+                if let Node::Lazy(lazy_old) = child_old {
+                    lazy_new.reuse(lazy_old);
+                }
+                return self.next_command();
+            }
+        }
+        let child_old = match child_old {
+            Node::Lazy(lazy_old) => lazy_old.into_resolved(),
+            child_old => child_old,
+        };
+        let child_new = child_new.resolve_lazy();
+
+        // Portals are handled like empty nodes by their parent.
+        let child_old = match child_old {
+            Node::Portal(portal_old) => {
+                if let Node::Portal(portal_new) = child_new {
+                    return Some(PatchCommand::PatchPortal {
+                        portal_old,
+                        portal_new,
+                    });
+                }
+                self.queued_commands
+                    .push_back(PatchCommand::RemovePortal { portal_old });
+                Node::Empty
+            }
+            child_old => child_old,
+        };
+        if let Node::Portal(portal_new) = child_new {
+            self.queued_commands
+                .push_back(PatchCommand::MountPortal { portal_new });
+            return self.remove(child_old);
+        }
+
         Some(match child_old {
             Node::Element(el_old) => match child_new {
                 Node::Element(el_new) => {
@@ -393,11 +455,17 @@ where
                 }
                 Node::Text(text_new) => PatchCommand::ReplaceElByText { el_old, text_new },
                 Node::Empty => PatchCommand::RemoveEl { el_old },
+                Node::Lazy(_) | Node::Portal(_) => {
+                    unreachable!("lazy nodes are resolved and portals handled")
+                }
             },
             Node::Text(text_old) => match child_new {
                 Node::Element(el_new) => PatchCommand::ReplaceTextByEl { text_old, el_new },
                 Node::Text(text_new) => PatchCommand::PatchText { text_old, text_new },
                 Node::Empty => PatchCommand::RemoveText { text_old },
+                Node::Lazy(_) | Node::Portal(_) => {
+                    unreachable!("lazy nodes are resolved and portals handled")
+                }
             },
             Node::Empty => match child_new {
                 Node::Element(el_new) => {
@@ -422,7 +490,13 @@ where
                     }
                 }
                 Node::Empty => return self.next_command(),
+                Node::Lazy(_) | Node::Portal(_) => {
+                    unreachable!("lazy nodes are resolved and portals handled")
+                }
             },
+            Node::Lazy(_) | Node::Portal(_) => {
+                unreachable!("lazy nodes are resolved and portals handled")
+            }
         })
     }
 
@@ -431,6 +505,8 @@ where
             Node::Element(el_old) => PatchCommand::RemoveEl { el_old },
             Node::Text(text_old) => PatchCommand::RemoveText { text_old },
             Node::Empty => return self.next_command(),
+            Node::Lazy(lazy_old) => return self.remove(lazy_old.into_resolved()),
+            Node::Portal(portal_old) => PatchCommand::RemovePortal { portal_old },
         })
     }
 }
@@ -444,7 +520,9 @@ where
     type Item = PatchCommand<'a, Ms>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.next_command()
+        self.queued_commands
+            .pop_front()
+            .or_else(|| self.next_command())
     }
 }
 
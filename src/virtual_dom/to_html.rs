@@ -0,0 +1,280 @@
+//! Rendering of virtual DOM nodes into HTML strings.
+//!
+//! It doesn't touch `web_sys` at all, so it works also outside of the browser -
+//! e.g. on a server when you want to prerender your `view` for SEO or static snapshots.
+
+use super::{At, AtValue, El, IntoNodes, Node, Tag, Text};
+use crate::browser::dom::Namespace;
+use std::borrow::Cow;
+use std::fmt;
+
+// https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+// https://html.spec.whatwg.org/multipage/syntax.html#raw-text-elements
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Render nodes into an HTML string.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let html = seed::virtual_dom::to_html::render_to_string(view(&model));
+/// ```
+pub fn render_to_string<Ms>(nodes: impl IntoNodes<Ms>) -> String {
+    nodes.into_nodes().iter().map(ToString::to_string).collect()
+}
+
+impl<Ms> fmt::Display for Node<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node(f, self, None, false)
+    }
+}
+
+impl<Ms> fmt::Display for El<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_el(f, self, None)
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&escape_text(&self.text))
+    }
+}
+
+fn write_node<Ms>(
+    f: &mut fmt::Formatter<'_>,
+    node: &Node<Ms>,
+    parent_namespace: Option<&Namespace>,
+    raw_text: bool,
+) -> fmt::Result {
+    match node {
+        Node::Element(el) => write_el(f, el, parent_namespace),
+        Node::Text(text) if raw_text => f.write_str(&escape_raw_text(&text.text)),
+        Node::Text(text) => write!(f, "{}", text),
+        // Portal's nodes aren't rendered in place.
+        Node::Empty | Node::Portal(_) => Ok(()),
+        Node::Lazy(lazy) => match lazy.node() {
+            Some(node) => write_node(f, node, parent_namespace, raw_text),
+            None => write_node(f, &lazy.render(), parent_namespace, raw_text),
+        },
+    }
+}
+
+fn write_el<Ms>(
+    f: &mut fmt::Formatter<'_>,
+    el: &El<Ms>,
+    parent_namespace: Option<&Namespace>,
+) -> fmt::Result {
+    let tag = el.tag.as_str();
+    let is_html = matches!(el.namespace, None | Some(Namespace::Html));
+    let is_textarea = is_html && el.tag == Tag::TextArea;
+
+    write!(f, "<{}", tag)?;
+
+    // `xmlns` is necessary only for the element where the namespace changes.
+    let namespace_changed = el.namespace.as_ref() != parent_namespace;
+    if let (Some(namespace), true) = (&el.namespace, namespace_changed) {
+        if namespace != &Namespace::Html {
+            write!(f, " xmlns=\"{}\"", escape_attr(namespace.as_str()))?;
+        }
+    }
+
+    let has_style = !el.style.vals.is_empty();
+    for (at, at_value) in &el.attrs.vals {
+        match at {
+            // Textarea's value is rendered as its content.
+            At::Value if is_textarea => continue,
+            // Style is rendered from `El::style` below.
+            At::Style if has_style => continue,
+            At::Custom(name) if name == "xmlns" && el.namespace.is_some() => continue,
+            _ => (),
+        }
+        match at_value {
+            AtValue::Ignored => (),
+            AtValue::None => write!(f, " {}", at.as_str())?,
+            AtValue::Some(value) => write!(f, " {}=\"{}\"", at.as_str(), escape_attr(value))?,
+        }
+    }
+    if has_style {
+        write!(f, " style=\"{}\"", escape_attr(&el.style.to_string()))?;
+    }
+
+    f.write_str(">")?;
+
+    if is_html && VOID_ELEMENTS.contains(&tag.to_ascii_lowercase().as_str()) {
+        return Ok(());
+    }
+
+    if is_textarea {
+        if let Some(AtValue::Some(value)) = el.attrs.vals.get(&At::Value) {
+            f.write_str(&escape_text(value))?;
+            return write!(f, "</{}>", tag);
+        }
+    }
+
+    let raw_text = is_html && RAW_TEXT_ELEMENTS.contains(&tag.to_ascii_lowercase().as_str());
+    for child in &el.children {
+        write_node(f, child, el.namespace.as_ref(), raw_text)?;
+    }
+
+    write!(f, "</{}>", tag)
+}
+
+/// Escape text so it can be safely used as an HTML element content.
+pub(crate) fn escape_text(text: &str) -> Cow<'_, str> {
+    escape(text, &['&', '<', '>'])
+}
+
+/// Escape closing tag sequences in the content of raw text elements (`script`, `style`)
+/// so the content can't close the element.
+/// - `</` is written as `<\/`, which is still valid in JS strings and CSS.
+fn escape_raw_text(text: &str) -> Cow<'_, str> {
+    if text.contains("</") {
+        Cow::Owned(text.replace("</", "<\\/"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Escape text so it can be safely used as a double-quoted HTML attribute value.
+pub(crate) fn escape_attr(value: &str) -> Cow<'_, str> {
+    escape(value, &['&', '<', '>', '"'])
+}
+
+fn escape<'a>(text: &'a str, special_chars: &[char]) -> Cow<'a, str> {
+    if !text.contains(special_chars) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' if special_chars.contains(&'<') => escaped.push_str("&lt;"),
+            '>' if special_chars.contains(&'>') => escaped.push_str("&gt;"),
+            '"' if special_chars.contains(&'"') => escaped.push_str("&quot;"),
+            _ => escaped.push(character),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Clone, Debug)]
+    enum Msg {
+        Clicked,
+    }
+
+    #[test]
+    fn nested_elements_and_text() {
+        let node: Node<Msg> = div![
+            C!["counter"],
+            "Tom & Jerry <3",
+            button!["Click", ev(Ev::Click, |_| Msg::Clicked)],
+        ];
+        assert_eq!(
+            node.to_string(),
+            r#"<div class="counter">Tom &amp; Jerry &lt;3<button>Click</button></div>"#
+        );
+    }
+
+    #[test]
+    fn attribute_values() {
+        let node: Node<Msg> = input![attrs! {
+            At::Value => r#"say "hi""#,
+            At::Disabled => AtValue::None,
+            At::Checked => false.as_at_value(),
+        }];
+        assert_eq!(
+            node.to_string(),
+            r#"<input value="say &quot;hi&quot;" disabled>"#
+        );
+    }
+
+    #[test]
+    fn void_elements() {
+        let node: Node<Msg> = p!["a", br![], "b", img![attrs! {At::Src => "a.png"}]];
+        assert_eq!(node.to_string(), r#"<p>a<br>b<img src="a.png"></p>"#);
+    }
+
+    #[test]
+    fn style() {
+        let node: Node<Msg> = span![style! {St::Display => "flex", St::Color => "red"}];
+        assert_eq!(
+            node.to_string(),
+            r#"<span style="display:flex;color:red"></span>"#
+        );
+    }
+
+    #[test]
+    fn textarea_value() {
+        let node: Node<Msg> = textarea![attrs! {At::Value => "</textarea>", At::Rows => 3}];
+        assert_eq!(
+            node.to_string(),
+            r#"<textarea rows="3">&lt;/textarea&gt;</textarea>"#
+        );
+    }
+
+    #[test]
+    fn raw_text_elements() {
+        let node: Node<Msg> = custom![Tag::from("style"), "a > b { color: red }"];
+        assert_eq!(node.to_string(), "<style>a > b { color: red }</style>");
+    }
+
+    #[test]
+    fn closing_tags_in_raw_text_elements() {
+        let node: Node<Msg> = custom![
+            Tag::from("script"),
+            r#"var html = "</script><img src=x onerror=alert(1)>";"#
+        ];
+        assert_eq!(
+            node.to_string(),
+            r#"<script>var html = "<\/script><img src=x onerror=alert(1)>";</script>"#
+        );
+
+        let node: Node<Msg> = custom![Tag::from("style"), "a::after { content: '</STYLE>' }"];
+        assert_eq!(
+            node.to_string(),
+            r#"<style>a::after { content: '<\/STYLE>' }</style>"#
+        );
+    }
+
+    #[test]
+    fn namespaces() {
+        let node: Node<Msg> = div![svg![
+            attrs! {At::ViewBox => "0 0 10 10"},
+            circle![attrs! {At::Cx => 5, At::Cy => 5, At::R => 4}],
+            linearGradient![],
+        ]];
+        assert_eq!(
+            node.to_string(),
+            concat!(
+                r#"<div><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">"#,
+                r#"<circle cx="5" cy="5" r="4"></circle>"#,
+                r#"<linearGradient></linearGradient></svg></div>"#
+            )
+        );
+
+        let mut math = El::<Msg>::empty(Tag::from("math"));
+        math.namespace = Some(Namespace::MathMl);
+        math.add_child(Node::new_text("x"));
+        assert_eq!(
+            math.to_string(),
+            r#"<math xmlns="http://www.w3.org/1998/mathml">x</math>"#
+        );
+    }
+
+    #[test]
+    fn render_nodes() {
+        let nodes: Vec<Node<Msg>> = vec![h1!["Title"], empty![], plain!["text"]];
+        assert_eq!(render_to_string(nodes), "<h1>Title</h1>text");
+    }
+}
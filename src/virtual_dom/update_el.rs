@@ -1,4 +1,4 @@
-use super::{Attrs, El, ElKey, ElRef, EventHandler, Node, Style, Tag, Text};
+use super::{Attrs, El, ElKey, ElRef, EventHandler, LifecycleHook, Node, Style, Tag, Text};
 
 // ------ Traits ------
 
@@ -42,6 +42,12 @@ impl<Ms> UpdateEl<Ms> for EventHandler<Ms> {
     }
 }
 
+impl<Ms> UpdateEl<Ms> for LifecycleHook<Ms> {
+    fn update_el(self, el: &mut El<Ms>) {
+        el.lifecycle_hooks.push(self)
+    }
+}
+
 impl<Ms> UpdateEl<Ms> for El<Ms> {
     fn update_el(self, el: &mut El<Ms>) {
         el.children.push(Node::Element(self))
@@ -200,14 +206,14 @@ mod tests {
 
     #[wasm_bindgen_test]
     fn update_el_event_handler() {
-        let event_handler: EventHandler<Ms> = ev(Ev::Click, |_| ());
+        let event_handler: EventHandler<Ms> = ev(Ev::Click, |_| None);
         let _el: Node<Ms> = div![event_handler];
         assert!(true);
     }
 
     #[wasm_bindgen_test]
     fn update_el_ref_event_handler() {
-        let event_handler: &EventHandler<Ms> = &ev(Ev::Click, |_| ());
+        let event_handler: &EventHandler<Ms> = &ev(Ev::Click, |_| None);
         let _el: Node<Ms> = div![event_handler];
         assert!(true);
     }